@@ -34,6 +34,52 @@ pub struct Args {
 
     #[command(flatten)]
     pub nmon_args: Option<NmonArgs>,
+
+    #[command(flatten)]
+    pub compare_args: CompareArgs,
+}
+
+#[derive(Parser, Debug, Clone)]
+#[command(next_help_heading = "Compare")]
+/// 子程序调用rnmon compare，与基线的测试结果对比
+pub struct CompareArgs {
+    /// 测试结束后与该基线测试结果目录对比JMeter各事务的统计值和各服务器的资源使用，生成res.compare.html，有指标超过容差时本次测试不通过(非0退出)
+    #[arg(long, value_name = "DIR")]
+    pub baseline: Option<PathBuf>,
+    /// 吞吐量下降超过该百分比时为回归，默认5
+    #[arg(long, value_name = "PCT")]
+    pub throughput_tolerance: Option<f64>,
+    /// 平均、p90、p95、p99响应时间上升超过该百分比时为回归，默认10
+    #[arg(long, value_name = "PCT")]
+    pub rt_tolerance: Option<f64>,
+    /// 错误率上升超过该百分点时为回归，默认0.5
+    #[arg(long, value_name = "PP")]
+    pub error_tolerance: Option<f64>,
+    /// CPU、内存、DISKBUSY使用率上升超过该百分点时为回归，默认10
+    #[arg(long, value_name = "PP")]
+    pub resource_tolerance: Option<f64>,
+}
+impl CompareArgs {
+    pub fn params(&self) -> Vec<String> {
+        let mut params = Vec::new();
+        if let Some(throughput_tolerance) = self.throughput_tolerance {
+            params.push("--throughput-tolerance".to_string());
+            params.push(throughput_tolerance.to_string());
+        }
+        if let Some(rt_tolerance) = self.rt_tolerance {
+            params.push("--rt-tolerance".to_string());
+            params.push(rt_tolerance.to_string());
+        }
+        if let Some(error_tolerance) = self.error_tolerance {
+            params.push("--error-tolerance".to_string());
+            params.push(error_tolerance.to_string());
+        }
+        if let Some(resource_tolerance) = self.resource_tolerance {
+            params.push("--resource-tolerance".to_string());
+            params.push(resource_tolerance.to_string());
+        }
+        params
+    }
 }
 
 #[derive(Parser, Debug, Clone)]
@@ -63,6 +109,12 @@ pub struct NmonArgs {
     /// 分析nmon文件时，设置对disk_busy使用进行分析
     #[arg(long, action = ArgAction::SetTrue)]
     pub disk_busy: bool,
+    /// DISKBUSY随时间变化的图表中，只显示按WAvg.排序最繁忙的前N个磁盘
+    #[arg(long, value_name = "N")]
+    pub disk_top: Option<usize>,
+    /// 只保留磁盘名称匹配REGEX的磁盘，对所有DISK开头的指标生效
+    #[arg(long, value_name = "REGEX")]
+    pub disk_filter: Option<String>,
     /// 分析nmon文件时，设置对mem使用进行分析
     #[arg(long, action = ArgAction::SetTrue)]
     pub mem_free: bool,
@@ -75,15 +127,69 @@ pub struct NmonArgs {
     /// 分析nmon文件时，设置对jfsfile使用进行分析
     #[arg(long, action = ArgAction::SetTrue)]
     pub jfsfile: bool,
-    /// 分析nmon文件时，设置对disk_io使用进行分析，暂不支持todo
+    /// 分析nmon文件时，设置对disk_io使用进行分析，即各磁盘的IO/sec
     #[arg(long, action = ArgAction::SetTrue)]
     pub disk_io: bool,
-    /// 分析nmon文件时，设置对disk_summary使用进行分析，暂不支持todo
+    /// 分析nmon文件时，设置对disk_summary使用进行分析，包括磁盘读写KB/s合计及各磁盘的平均值
     #[arg(long, action = ArgAction::SetTrue)]
     pub disk_summary: bool,
-    /// 分析nmon文件时，设置对net使用进行分析，不支持todo
+    /// 分析nmon文件时，设置对网络使用进行分析，包括NET和NETPACKET
     #[arg(long, action = ArgAction::SetTrue)]
     pub net: bool,
+    /// 分析nmon文件时，设置对TOP进程进行分析，按命令名称汇总各进程的%CPU和ResData
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub top: bool,
+    /// TOP进程的图表和表格中，只显示前N个命令
+    #[arg(long, value_name = "N")]
+    pub top_num: Option<usize>,
+    /// 分析nmon文件时，设置对VM使用进行分析，包括换页和缺页
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub vm: bool,
+    /// 分析nmon文件时，设置对PROC使用进行分析，包括运行队列与CPU数量对比、pswitch、syscall、fork
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub proc: bool,
+    /// 生成HTML图表时，增加多个服务器的对比图，同一指标每个服务器一条线
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub compare: bool,
+    /// 自定义nmon图表，可指定多次，格式为: SECTION:COLUMN[,COLUMN...][:KIND]，比如: --chart "DISKBUSY:*:awmn"
+    #[arg(long, value_name = "SPEC", action = ArgAction::Append, requires = "html")]
+    pub chart: Option<Vec<String>>,
+    /// nmon报告定义文件(TOML或JSON)，指定HTML报告里的图表、顺序和标题
+    #[arg(long, value_name = "FILE", requires = "html")]
+    pub report: Option<PathBuf>,
+    /// nmon阈值规则，可指定多次，比如: --rule "CPU_ALL:User%+Sys% mean < 70"，有规则不通过时本次测试不通过(非0退出)
+    #[arg(long, value_name = "RULE", action = ArgAction::Append)]
+    pub rule: Option<Vec<String>>,
+    /// nmon阈值规则文件，每行一个规则
+    #[arg(long, value_name = "FILE")]
+    pub rules_file: Option<String>,
+    /// nmon文件里ZZZZ时间序列所在的时区(即服务器的时区)，可以是固定时区(+08:00)或IANA时区名称(Asia/Shanghai)
+    #[arg(long, value_name = "TZ")]
+    pub tz: Option<String>,
+    /// 单独指定某些nmon文件的时区，格式为: REGEX=TZ，REGEX匹配nmon文件名，可指定多次
+    #[arg(long, value_name = "REGEX=TZ", action = ArgAction::Append)]
+    pub tz_file: Option<Vec<String>>,
+    /// 只分析该时间之后的数据，可以是偏移量(+60s、-30s)或时间(2020-06-16 17:41:00)
+    #[arg(long, value_name = "TIME", allow_hyphen_values = true)]
+    pub from: Option<String>,
+    /// 只分析该时间之前的数据，格式同--from
+    #[arg(long, value_name = "TIME", allow_hyphen_values = true)]
+    pub to: Option<String>,
+    /// 只分析JMeter线程加载完成后的稳定运行时间段，即按--rampup和--duration计算--from和--to，与--from/--to同时指定时以--from/--to为准
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub steady: bool,
+    /// 分析nmon文件时使用宽松模式，跳过无法解析的数据或文件，而不是报错退出
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub lenient: bool,
+    /// 生成HTML图表时，把echarts库和图表数据内嵌到一个index_nmons.html文件里，方便邮件发送或附加到工单
+    #[arg(long, action = ArgAction::SetTrue, requires = "html")]
+    pub single_file: bool,
+    /// --single-file时，图表数据gzip压缩后以base64内嵌，文件更小
+    #[arg(long, action = ArgAction::SetTrue, requires = "single_file")]
+    pub compress_data: bool,
+    /// 生成HTML图表时，每条曲线最多的点数，超过时用LTTB降采样，保留峰值，比如: --max-points 2000
+    #[arg(long, value_name = "N")]
+    pub max_points: Option<u64>,
 }
 impl NmonArgs {
     pub fn params(&self) -> Vec<String> {
@@ -98,6 +204,14 @@ impl NmonArgs {
         if self.disk_busy {
             params.push("--disk-busy".to_string());
         }
+        if let Some(disk_top) = self.disk_top {
+            params.push("--disk-top".to_string());
+            params.push(disk_top.to_string());
+        }
+        if let Some(ref disk_filter) = self.disk_filter {
+            params.push("--disk-filter".to_string());
+            params.push(disk_filter.clone());
+        }
         if self.mem_free {
             params.push("--mem-free".to_string());
         }
@@ -116,6 +230,76 @@ impl NmonArgs {
         if self.disk_summary {
             params.push("--disk-summary".to_string());
         }
+        if self.net {
+            params.push("--net".to_string());
+        }
+        if self.top {
+            params.push("--top".to_string());
+        }
+        if let Some(top_num) = self.top_num {
+            params.push("--top-num".to_string());
+            params.push(top_num.to_string());
+        }
+        if self.vm {
+            params.push("--vm".to_string());
+        }
+        if self.proc {
+            params.push("--proc".to_string());
+        }
+        if self.compare {
+            params.push("--compare".to_string());
+        }
+        if let Some(ref chart) = self.chart {
+            chart.iter().for_each(|c| {
+                params.push("--chart".to_string());
+                params.push(c.clone());
+            });
+        }
+        if let Some(ref report) = self.report {
+            params.push("--report".to_string());
+            params.push(report.display().to_string());
+        }
+        if let Some(ref rule) = self.rule {
+            rule.iter().for_each(|r| {
+                params.push("--rule".to_string());
+                params.push(r.clone());
+            });
+        }
+        if let Some(ref rules_file) = self.rules_file {
+            params.push("--rules-file".to_string());
+            params.push(rules_file.clone());
+        }
+        if let Some(ref tz) = self.tz {
+            params.push("--tz".to_string());
+            params.push(tz.clone());
+        }
+        if let Some(ref tz_file) = self.tz_file {
+            tz_file.iter().for_each(|t| {
+                params.push("--tz-file".to_string());
+                params.push(t.clone());
+            });
+        }
+        if let Some(ref from) = self.from {
+            params.push("--from".to_string());
+            params.push(from.clone());
+        }
+        if let Some(ref to) = self.to {
+            params.push("--to".to_string());
+            params.push(to.clone());
+        }
+        if self.lenient {
+            params.push("--lenient".to_string());
+        }
+        if self.single_file {
+            params.push("--single-file".to_string());
+        }
+        if self.compress_data {
+            params.push("--compress-data".to_string());
+        }
+        if let Some(max_points) = self.max_points {
+            params.push("--max-points".to_string());
+            params.push(max_points.to_string());
+        }
 
         params
     }
@@ -271,4 +271,13 @@ impl JMeter {
         (interval, count)
     }
 
+    /// 稳定运行时间段，相对nmon监控开始的偏移量，即去掉jmeter启动时间(3秒)和线程加载时间(rampup)，
+    /// 返回rnmon的--from和--to参数值
+    pub fn steady_window(&self) -> (String, String) {
+        let from = self.args.rampup + 3;
+        let to = from + self.args.duration;
+        debug!("动态计算，稳定运行时间段: +{}s ~ +{}s", from, to);
+        (format!("+{}s", from), format!("+{}s", to))
+    }
+
 }
@@ -11,11 +11,17 @@ use log::error;
 use std::path::PathBuf;
 use std::process::Child;
 use std::process::Command;
+use std::process::ExitStatus;
 use std::time::SystemTime;
 
 use crate::client::Args;
 use crate::jmeter::JMeter;
 
+/// rnmon阈值规则不通过时的退出码
+const RNMON_RULE_FAILED_EXIT_CODE: i32 = 3;
+/// rnmon compare有指标超过容差时的退出码
+const RNMON_REGRESSION_EXIT_CODE: i32 = 4;
+
 fn main() -> Result<()> {
     // 处理传入的程序的参数
     let cli_args = Args::parse();
@@ -49,6 +55,7 @@ fn main() -> Result<()> {
     let jmeter_args = cli_args.jmeter_args.clone();
     let ssh_args = cli_args.ssh_args.clone();
     let nmon_args = cli_args.nmon_args.clone();
+    let compare_args = cli_args.compare_args.clone();
     debug!("cli_args: {:?}", cli_args);
     debug!("ssh_args: {:?}", ssh_args);
     debug!("nmon_args: {:?}", nmon_args);
@@ -57,6 +64,10 @@ fn main() -> Result<()> {
 
     // let mut server_nmon_dir = PathBuf::new();
     let mut server_nmon_file = String::new();
+    // nmon阈值规则是否不通过
+    let mut rule_failed = false;
+    // 与基线对比是否有回归
+    let mut regression = false;
 
     let child_rssh = if ssh_args.nmon {
         let ssh_dir = ssh_args.ssh_dir.clone().expect("调用rssh程序时其ssh_dir参数无值");
@@ -126,36 +137,94 @@ fn main() -> Result<()> {
         if let Some(nmon_args) = nmon_args.clone() {
             info!("分析nmon文件...");
             let mut params = nmon_args.params();
+            // 按rampup和duration只分析稳定运行时间段
+            if nmon_args.steady && nmon_args.from.is_none() && nmon_args.to.is_none() {
+                let (from, to) = jmeter.steady_window();
+                params.push("--from".to_string());
+                params.push(from);
+                params.push("--to".to_string());
+                params.push(to);
+            }
             // 增加分析后的结果目录
             params.push("--html-output".to_string());
             params.push(jmeter_output_dir.display().to_string());
+            // 在nmon图表里对照JMeter的TPS、响应时间和错误率
+            let jtl = jmeter_output_dir.join("res.jtl");
+            if nmon_args.html && jtl.is_file() {
+                params.push("--jtl".to_string());
+                params.push(jtl.display().to_string());
+            }
             // 增加分析的nmon目录
             params.push(local_nmon_dir);
-            call_command("rnmon", params)?;
+            let status = call_command("rnmon", params)?;
+            // rnmon阈值规则不通过时退出码为3
+            if status.code() == Some(RNMON_RULE_FAILED_EXIT_CODE) {
+                error!("nmon阈值规则不通过，详见: {}", jmeter_output_dir.join("res.rules.txt").display());
+                rule_failed = true;
+            }
         }
 
         info!("删除远程服务器上的nmon文件...");
         call_command( "rssh", vec![ "exec".to_string(), "--".to_string(), "rm -r".to_string(), server_nmon_file, ], )?;
     }
 
+    if let Some(ref baseline) = compare_args.baseline {
+        info!("与基线{}对比...", baseline.display());
+        let mut params = vec!["compare".to_string(), baseline.display().to_string(), jmeter_output_dir.display().to_string()];
+        params.append(&mut compare_args.params());
+        // 与nmon分析的时间段一致
+        if let Some(ref nmon_args) = nmon_args {
+            let (from, to) = if nmon_args.steady && nmon_args.from.is_none() && nmon_args.to.is_none() {
+                let (from, to) = jmeter.steady_window();
+                (Some(from), Some(to))
+            } else {
+                (nmon_args.from.clone(), nmon_args.to.clone())
+            };
+            if let Some(from) = from {
+                params.push("--from".to_string());
+                params.push(from);
+            }
+            if let Some(to) = to {
+                params.push("--to".to_string());
+                params.push(to);
+            }
+            if nmon_args.lenient {
+                params.push("--lenient".to_string());
+            }
+        }
+        params.push("--output".to_string());
+        params.push(jmeter_output_dir.display().to_string());
+        let status = call_command("rnmon", params)?;
+        if status.code() == Some(RNMON_REGRESSION_EXIT_CODE) {
+            error!("与基线对比有回归，详见: {}", jmeter_output_dir.join("res.compare.html").display());
+            regression = true;
+        }
+    }
+
     let logfile = cli_args.logfile.clone().unwrap();
     std::fs::copy(&logfile, jmeter_output_dir.join("run.log"))?;
     std::fs::remove_file(logfile)?;
 
     info!( "运行结束，结果数据在此目录下: {}", jmeter_output_dir.display() );
 
+    if rule_failed {
+        return Err(anyhow!("系统资源使用不满足nmon阈值规则，本次测试不通过"));
+    }
+    if regression {
+        return Err(anyhow!("与基线对比有指标超过容差，本次测试不通过"));
+    }
     Ok(())
 }
 
-fn call_command(name: &str, args: Vec<String>) -> Result<()> {
+fn call_command(name: &str, args: Vec<String>) -> Result<ExitStatus> {
     let mut command = Command::new(name);
     command.args(args);
     info!("调用子命令程序: {}, 其参数:{:?}", name, &command);
-    command
+    let status = command
         .spawn()
         .map_err(|e| anyhow!("调用子命令程序失败: {}， 子程序: {:?} ", e, command))?
         .wait()?;
-    Ok(())
+    Ok(status)
 }
 fn call_command_nowait(name: &str, args: Vec<String>) -> Result<Child> {
     let mut command = Command::new(name);
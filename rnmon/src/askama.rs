@@ -7,15 +7,39 @@ use ndarray::Array1;
 
 
 use askama::Template;
+use base64::Engine;
+use base64::prelude::BASE64_STANDARD;
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use std::io::Write;
+use html::ChartsName;
 
 use std::collections::BTreeMap;
 pub use html::NmonHtmlTemplate;
 pub use html::NmonFile;
 pub use html::Chart;
+pub use html::InlineScripts;
 
 use super::Measurement;
 
 use super::NmonData;
+use super::jtl::Jtl;
+use super::chart::ChartKind;
+use super::chart::ChartSpec;
+
+/// --single-file时内嵌到HTML里的脚本，避免脚本里的</script>提前结束<script>标签。
+/// compress为true时图表数据gzip压缩后转为base64，由浏览器的DecompressionStream解压
+pub fn inline_scripts(echarts: &str, data: &str, compress: bool) -> InlineScripts {
+    let escape = |js: &str| js.replace("</script", "<\\/script");
+    let data = if compress {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(data.as_bytes()).unwrap();
+        BASE64_STANDARD.encode(encoder.finish().unwrap())
+    } else {
+        escape(data)
+    };
+    InlineScripts::new(escape(echarts), data, compress)
+}
 
 pub fn js_echarts() -> String {
     let echarts = js::Echarts::new();
@@ -24,6 +48,9 @@ pub fn js_echarts() -> String {
 }
 
 
+/// 图表X轴时间的格式，带时区，浏览器按本地时区显示，不同时区的服务器的时间才能对齐
+pub const POINT_X_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%:z";
+
 #[derive(Clone, Debug)]
 // pub struct TimeSeriesData {
 pub struct Point {
@@ -47,17 +74,25 @@ trait ToJStr: Template {
     }
 }
 
+/// 报告里的一个nmon文件
+pub type HtmlNmonFile = (String, String, Vec<(String, String)>);
+
 // pub fn html(template_data: Vec<(String, String)>) -> String {
-pub fn html(template_data: Vec<(String, String)>, charts: Vec<Chart>) -> String {
+/// template_data: (nmon数据文件id, nmon文件名, 服务器信息)
+/// compares: 多个nmon文件的对比图
+/// refresh: watch时每refresh秒重新加载图表数据
+/// inline: --single-file时内嵌的echarts库和图表数据
+/// title: 报告定义文件里的报告标题
+pub fn html(template_data: Vec<HtmlNmonFile>, charts: Vec<Chart>, compares: Vec<Chart>, refresh: Option<u64>, inline: Option<InlineScripts>, title: Option<&str>) -> String {
 
     let mut nmonfiles = Vec::new();
-    for (id, name) in template_data.into_iter() {
+    for (id, name, host) in template_data.into_iter() {
         nmonfiles.push(
-            NmonFile::new(id, name, "selected".to_string())
+            NmonFile::new(id, name, "selected".to_string(), host)
         )
     }
 
-    let html = NmonHtmlTemplate::new(nmonfiles, charts);
+    let html = NmonHtmlTemplate::new(nmonfiles, charts, compares, refresh, inline, title.map(|t| t.to_string()));
 
     html.render().unwrap()
 }
@@ -74,17 +109,15 @@ pub fn js_system_summary(data: &NmonData) -> String {
     let cpu_idle = cpu_all.column("Idle%").unwrap();
     let cpu_used = Array1::from_vec(vec![100.0; cpu_idle.len()]) - cpu_idle;
     // let zzzz = data.xalis_datetime_to_own();
-    let zzzz = cpu_all.zzzz();
-    let series_data_cpu: Vec<Point> = zzzz.iter().zip(cpu_used.iter())
-        .map(|(x, y)|
-            Point::new(
-                format!("{}", x.format("%Y-%m-%d %H:%M:%S")),
-                *y
-            )
-        ).collect();
 
-    let disk_xfer = data.measurement("DISKXFER").unwrap();
-    let series_data_io = disk_xfer.column_sum_echartjs_overtime();
+    // CPU和磁盘IO按相同的时间点降采样，没有DISKXFER时只有CPU曲线
+    let (series_data_cpu, series_data_io) = match data.measurement("DISKXFER") {
+        Some(disk_xfer) => {
+            let mut series = cpu_all.points_shared(&[cpu_used.to_vec(), disk_xfer.sum_overtime().to_vec()]).into_iter();
+            (series.next().unwrap(), series.next().unwrap())
+        },
+        None => (cpu_all.points(&cpu_used.to_vec()), Vec::new()),
+    };
 
     let b = js::SystemSum::new(
         data.filename().to_string(),
@@ -165,7 +198,9 @@ pub fn js_cpu_all(data: &NmonData) -> String {
 
 
 pub fn js_jfsfile(data: &NmonData) -> String {
-    let measurement = data.measurement("JFSFILE").unwrap();
+    let Some(measurement) = data.measurement("JFSFILE") else {
+        return js_missing_section(&ChartsName::JFSFILE, data, "JFSFILE");
+    };
     let series_data = measurement.columns_echartjs_overtime();
 
     let b = js::JfsFile::new(
@@ -180,9 +215,13 @@ pub fn js_jfsfile(data: &NmonData) -> String {
 
 
 pub fn js_mem_free(data: &NmonData) -> String {
-    let measurement = data.measurement("MEM").unwrap();
+    let Some(measurement) = data.measurement("MEM") else {
+        return js_missing_section(&ChartsName::MEM_FREE, data, "MEM");
+    };
 
-    let series_data_total = measurement.column_echartjs_overtime("memtotal").unwrap();
+    let Some(series_data_total) = measurement.column_echartjs_overtime("memtotal") else {
+        return js_missing_column(&ChartsName::MEM_FREE, data, "MEM", "memtotal");
+    };
     let series_data_other = measurement.column_echartjs_vec(&["memfree", "cached", "buffers"]);
 
     let b = js::MemFree::new(
@@ -200,9 +239,13 @@ pub fn js_mem_free(data: &NmonData) -> String {
 
 
 pub fn js_mem_swap(data: &NmonData) -> String {
-    let measurement = data.measurement("MEM").unwrap();
+    let Some(measurement) = data.measurement("MEM") else {
+        return js_missing_section(&ChartsName::MEM_SWAP, data, "MEM");
+    };
 
-    let series_data_total = measurement.column_echartjs_overtime("swaptotal").unwrap();
+    let Some(series_data_total) = measurement.column_echartjs_overtime("swaptotal") else {
+        return js_missing_column(&ChartsName::MEM_SWAP, data, "MEM", "swaptotal");
+    };
     let series_data_other = measurement.column_echartjs_vec(&["swapfree", "swapcached",]);
 
     let b = js::MemSwap::new(
@@ -225,9 +268,13 @@ pub fn js_mem_swap(data: &NmonData) -> String {
 
 
 pub fn js_mem_active(data: &NmonData) -> String {
-    let measurement = data.measurement("MEM").unwrap();
+    let Some(measurement) = data.measurement("MEM") else {
+        return js_missing_section(&ChartsName::MEM_ACTIVE, data, "MEM");
+    };
 
-    let series_data_total = measurement.column_echartjs_overtime("memtotal").unwrap();
+    let Some(series_data_total) = measurement.column_echartjs_overtime("memtotal") else {
+        return js_missing_column(&ChartsName::MEM_ACTIVE, data, "MEM", "memtotal");
+    };
     let series_data_other = measurement.column_echartjs_vec(&["active", "inactive",]);
 
     let b = js::MemActive::new(
@@ -250,7 +297,9 @@ pub fn js_mem_active(data: &NmonData) -> String {
 
 
 pub fn js_diskbusy_awmn(data: &NmonData) -> String {
-    let measurement = data.measurement("DISKBUSY").unwrap();
+    let Some(measurement) = data.measurement("DISKBUSY") else {
+        return js_missing_section(&ChartsName::DISKBUSY_AWMN, data, "DISKBUSY");
+    };
 
     let axis_label = measurement.header().clone();
     let data_avg = measurement.rows_mean();
@@ -271,3 +320,543 @@ pub fn js_diskbusy_awmn(data: &NmonData) -> String {
     );
     b.to_js_str()
 }
+
+
+
+
+
+
+
+
+
+
+/// 只显示按WAvg.排序最繁忙的前top个磁盘，磁盘太多时图表才看得清
+pub fn js_diskbusy_overtime(data: &NmonData, top: usize) -> String {
+    let Some(measurement) = data.measurement("DISKBUSY") else {
+        return js_missing_section(&ChartsName::DISKBUSY_OVERTIME, data, "DISKBUSY");
+    };
+
+    let mut disks: Vec<(&String, f32)> = measurement.header().iter().zip(measurement.rows_wavg()).collect();
+    disks.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let columns: Vec<&str> = disks.iter().take(top).map(|(name, _)| name.as_str()).collect();
+    let series_data = measurement.column_echartjs_vec(&columns);
+
+    let b = js::DiskBusyOvertime::new(
+        data.filename().to_string(),
+        series_data,
+    );
+    b.to_js_str()
+}
+
+
+
+
+
+pub fn js_disk_summ_overtime(data: &NmonData) -> String {
+    let Some(disk_read) = data.measurement("DISKREAD") else {
+        return js_missing_section(&ChartsName::DISK_SUMM, data, "DISKREAD");
+    };
+    let Some(disk_write) = data.measurement("DISKWRITE") else {
+        return js_missing_section(&ChartsName::DISK_SUMM, data, "DISKWRITE");
+    };
+    let Some(disk_xfer) = data.measurement("DISKXFER") else {
+        return js_missing_section(&ChartsName::DISK_SUMM, data, "DISKXFER");
+    };
+    // 读、写、IO三条曲线按相同的时间点降采样
+    let mut series = disk_read
+        .points_shared(&[
+            disk_read.sum_overtime().to_vec(),
+            disk_write.sum_overtime().to_vec(),
+            disk_xfer.sum_overtime().to_vec(),
+        ])
+        .into_iter();
+    let (series_data_read, series_data_write, series_data_io) =
+        (series.next().unwrap(), series.next().unwrap(), series.next().unwrap());
+
+    let b = js::DiskSummOvertime::new(
+        data.filename().to_string(),
+        series_data_read,
+        series_data_write,
+        series_data_io,
+    );
+    b.to_js_str()
+}
+
+
+
+
+
+/// DISKREAD、DISKWRITE、DISKXFER、DISKBSIZE各磁盘的Avg/WAvg/Max/Min柱状图，与js_diskbusy_awmn相同
+pub fn js_disk_summ_awmn(data: &NmonData, chart: &ChartsName, name: &str) -> String {
+    let Some(measurement) = data.measurement(name) else {
+        return js_missing_section(chart, data, name);
+    };
+    let title = match name {
+        "DISKREAD" => "Disk Read KB/s",
+        "DISKWRITE" => "Disk Write KB/s",
+        "DISKXFER" => "Disk transfers per second",
+        "DISKBSIZE" => "Disk Block Size KB",
+        _ => name,
+    };
+
+    let axis_label = measurement.header().clone();
+    let data_avg = measurement.rows_mean();
+    // echarts.js 堆叠柱状图时值不叠加
+    let mut data_wavg= measurement.rows_wavg();
+    data_wavg = data_wavg.iter().zip(data_avg.iter()).map(|(wavg, avg)| *wavg - *avg).collect();
+
+    let data_max = measurement.rows_max();
+    let data_min = measurement.rows_min();
+
+    let b = js::DiskSummAwmn::new(
+        title,
+        data.filename().to_string(),
+        axis_label,
+        data_avg,
+        data_wavg,
+        data_max,
+        data_min,
+    );
+    b.to_js_str()
+}
+
+
+
+
+
+
+
+
+
+
+// 写的数据取负值，图表上读在X轴上方，写在X轴下方
+fn negative(points: Vec<Point>) -> Vec<Point> {
+    points.into_iter().map(|p| Point::new(p.x, -p.y)).collect()
+}
+
+pub fn js_network_io_total(data: &NmonData) -> String {
+    let Some(measurement) = data.measurement("NET") else {
+        return js_missing_section(&ChartsName::NET_TOTAL, data, "NET");
+    };
+
+    // 回环网卡lo的流量不经过物理网络，不计入总量
+    let read: Vec<&str> = measurement.header_ends_with("-read-KB/s").into_iter().filter(|h| !h.starts_with("lo-")).collect();
+    let write: Vec<&str> = measurement.header_ends_with("-write-KB/s").into_iter().filter(|h| !h.starts_with("lo-")).collect();
+    let series_data_read = measurement.columns_sum_echartjs_overtime(&read);
+    let series_data_write_ve = negative(measurement.columns_sum_echartjs_overtime(&write));
+
+    let b = js::NetworkIOTotal::new(
+        data.filename().to_string(),
+        series_data_read,
+        series_data_write_ve,
+    );
+    b.to_js_str()
+}
+
+
+
+
+
+pub fn js_network_io_device(data: &NmonData) -> String {
+    let Some(measurement) = data.measurement("NET") else {
+        return js_missing_section(&ChartsName::NET_DEVICE, data, "NET");
+    };
+
+    let mut series_data = measurement.column_echartjs_vec(&measurement.header_ends_with("-read-KB/s"));
+    for (name, points) in measurement.column_echartjs_vec(&measurement.header_ends_with("-write-KB/s")) {
+        series_data.push((name, negative(points)));
+    }
+
+    let b = js::NetworkIODevice::new(
+        data.filename().to_string(),
+        series_data,
+    );
+    b.to_js_str()
+}
+
+
+
+
+
+pub fn js_network_packet(data: &NmonData) -> String {
+    let Some(measurement) = data.measurement("NETPACKET") else {
+        return js_missing_section(&ChartsName::NET_PACKET, data, "NETPACKET");
+    };
+
+    let mut series_data = measurement.column_echartjs_vec(&measurement.header_ends_with("-read/s"));
+    series_data.append(&mut measurement.column_echartjs_vec(&measurement.header_ends_with("-write/s")));
+
+    let b = js::NetworkPacket::new(
+        data.filename().to_string(),
+        series_data,
+    );
+    b.to_js_str()
+}
+
+
+
+
+
+pub fn js_top_cpu(data: &NmonData, top: usize) -> String {
+    let Some(processes) = data.top() else {
+        return js_missing_section(&ChartsName::TOP_CPU, data, "TOP");
+    };
+    let series_data = processes.echartjs_overtime(processes.cpu(), top, 1.0);
+
+    let b = js::TopProcess::new(
+        "Top Processes by %CPU",
+        "%CPU",
+        data.filename().to_string(),
+        series_data,
+    );
+    b.to_js_str()
+}
+
+
+
+
+
+pub fn js_top_res_data(data: &NmonData, top: usize) -> String {
+    let Some(processes) = data.top() else {
+        return js_missing_section(&ChartsName::TOP_RESDATA, data, "TOP");
+    };
+    // nmon TOP里ResData的单位为KB，转换为MB与MEM图表一致
+    let series_data = processes.echartjs_overtime(processes.res_data(), top, 1024.0);
+
+    let b = js::TopProcess::new(
+        "Top Processes by ResData MB",
+        "MB",
+        data.filename().to_string(),
+        series_data,
+    );
+    b.to_js_str()
+}
+
+
+
+
+
+
+
+
+
+
+pub fn js_vm_paging(data: &NmonData) -> String {
+    let Some(measurement) = data.measurement("VM") else {
+        return js_missing_section(&ChartsName::VM_PAGING, data, "VM");
+    };
+    let series_data = measurement.column_echartjs_vec(&["pgpgin", "pgpgout", "pswpin", "pswpout", "pgmajfault"]);
+
+    let b = js::VmPaging::new(
+        data.filename().to_string(),
+        series_data,
+    );
+    b.to_js_str()
+}
+
+
+
+
+
+pub fn js_proc_runqueue(data: &NmonData) -> String {
+    let Some(measurement) = data.measurement("PROC") else {
+        return js_missing_section(&ChartsName::PROC_RUNQUEUE, data, "PROC");
+    };
+    let series_data = measurement.column_echartjs_vec(&["Runnable", "Blocked"]);
+
+    // CPU数量优先取CPU_ALL里的CPUs列，没有时取nmon文件头里的cpus
+    // 按PROC的时间点降采样，与运行队列的曲线对齐
+    let series_data_cpus = match data.measurement("CPU_ALL").and_then(|cpu_all| cpu_all.column("CPUs")) {
+        Some(cpus) => measurement.points(&cpus.to_vec()),
+        None => {
+            let cpus = data.host().cpus().and_then(|cpus| cpus.parse::<f32>().ok()).unwrap_or(0.);
+            measurement.points(&vec![cpus; measurement.zzzz().len()])
+        },
+    };
+
+    let b = js::ProcRunQueue::new(
+        data.filename().to_string(),
+        series_data,
+        series_data_cpus,
+    );
+    b.to_js_str()
+}
+
+
+
+
+
+pub fn js_proc_rates(data: &NmonData) -> String {
+    let Some(measurement) = data.measurement("PROC") else {
+        return js_missing_section(&ChartsName::PROC_RATES, data, "PROC");
+    };
+    let series_data = measurement.column_echartjs_vec(&["pswitch", "syscall"]);
+    let series_data_fork = measurement.column_echartjs_overtime("fork").unwrap_or_default();
+
+    let b = js::ProcRates::new(
+        data.filename().to_string(),
+        series_data,
+        series_data_fork,
+    );
+    b.to_js_str()
+}
+
+
+
+
+
+
+/// 对比图里各nmon文件的名称，优先使用AAA里的host，host重复时使用文件名
+pub(crate) fn compare_names(datas: &[NmonData]) -> Vec<String> {
+    let hosts: Vec<Option<&str>> = datas.iter().map(|data| data.host().host()).collect();
+    datas
+        .iter()
+        .zip(hosts.iter())
+        .map(|(data, host)| match host {
+            Some(host) if hosts.iter().filter(|h| **h == Some(*host)).count() == 1 => host.to_string(),
+            _ => data.filename().to_string(),
+        })
+        .collect()
+}
+
+fn points(measurement: &Measurement, array1: &Array1<f32>) -> Vec<Point> {
+    measurement.points(&array1.to_vec())
+}
+
+/// 各服务器的CPU使用率，即100 - Idle%
+pub fn js_compare_cpu(datas: &[NmonData]) -> String {
+    let series_data = datas
+        .iter()
+        .zip(compare_names(datas))
+        .filter_map(|(data, name)| {
+            let cpu_all = data.measurement("CPU_ALL")?;
+            let cpu_used = cpu_all.column("Idle%")?.mapv(|idle| 100.0 - idle);
+            Some((name, points(cpu_all, &cpu_used)))
+        })
+        .collect();
+    js::Compare::new("CPU Used%", "%", series_data).to_js_str()
+}
+
+/// 各服务器的空闲内存memfree
+pub fn js_compare_mem_free(datas: &[NmonData]) -> String {
+    let series_data = datas
+        .iter()
+        .zip(compare_names(datas))
+        .filter_map(|(data, name)| {
+            let measurement = data.measurement("MEM")?;
+            // AIX的MEM没有memfree，取Real free(MB)
+            let points = measurement
+                .column_echartjs_overtime("memfree")
+                .or_else(|| measurement.column_echartjs_overtime("Real free(MB)"))?;
+            Some((name, points))
+        })
+        .collect();
+    js::Compare::new("MEM memfree", "MB", series_data).to_js_str()
+}
+
+/// 各服务器每个时间点最繁忙的磁盘的DISKBUSY
+pub fn js_compare_diskbusy(datas: &[NmonData]) -> String {
+    let series_data = datas
+        .iter()
+        .zip(compare_names(datas))
+        .filter_map(|(data, name)| {
+            let measurement = data.measurement("DISKBUSY")?;
+            Some((name, points(measurement, &measurement.max_overtime())))
+        })
+        .collect();
+    js::Compare::new("DISKBUSY Max", "%", series_data).to_js_str()
+}
+
+/// 各服务器所有网卡(不包括回环网卡lo)读写KB/s的合计
+pub fn js_compare_net(datas: &[NmonData]) -> String {
+    let series_data = datas
+        .iter()
+        .zip(compare_names(datas))
+        .filter_map(|(data, name)| {
+            let measurement = data.measurement("NET")?;
+            let columns: Vec<&str> = measurement
+                .header()
+                .iter()
+                .map(|h| h.as_str())
+                .filter(|h| !h.starts_with("lo-") && (h.ends_with("-read-KB/s") || h.ends_with("-write-KB/s")))
+                .collect();
+            Some((name, measurement.columns_sum_echartjs_overtime(&columns)))
+        })
+        .collect();
+    js::Compare::new("Network I/O Read+Write", "KB/sec", series_data).to_js_str()
+}
+
+
+
+
+
+
+/// 图表不适用于该nmon文件的平台时，只显示标题和原因
+pub fn js_not_applicable(chart: &ChartsName, data: &NmonData) -> String {
+    let reason = format!("该图表不适用于{}平台的nmon文件", data.host().platform());
+    js::NotApplicable::new(&chart.to_string(), data.filename().to_string(), reason).to_js_str()
+}
+
+/// nmon文件里没有图表需要的指标时(比如nmon未采集，或该指标没有数据)，只显示标题和原因
+pub fn js_missing_section(chart: &ChartsName, data: &NmonData, section: &str) -> String {
+    let reason = format!("该nmon文件没有{}指标", section);
+    js::NotApplicable::new(&chart.to_string(), data.filename().to_string(), reason).to_js_str()
+}
+
+/// 指标里没有图表需要的列时(比如AIX的MEM没有memtotal)，只显示标题和原因
+pub fn js_missing_column(chart: &ChartsName, data: &NmonData, section: &str, column: &str) -> String {
+    let reason = format!("该nmon文件的{}指标没有{}列", section, column);
+    js::NotApplicable::new(&chart.to_string(), data.filename().to_string(), reason).to_js_str()
+}
+
+/// AIX LPAR的物理CPU使用与授权(entitled)，以及授权使用率
+pub fn js_aix_lpar(data: &NmonData) -> String {
+    let Some(measurement) = data.measurement("LPAR") else {
+        return js_not_applicable(&ChartsName::AIX_LPAR, data);
+    };
+    let series_data = measurement.column_echartjs_vec(&["PhysicalCPU", "entitled", "virtualCPUs"]);
+    let series_data_ec = match (measurement.column("PhysicalCPU"), measurement.column("entitled")) {
+        (Some(physical), Some(entitled)) => {
+            let ec = ndarray::Zip::from(&physical).and(&entitled)
+                .map_collect(|p, e| if *e == 0. { 0. } else { p / e * 100. });
+            points(measurement, &ec)
+        }
+        _ => Vec::new(),
+    };
+
+    let b = js::AixLpar::new(
+        data.filename().to_string(),
+        series_data,
+        series_data_ec,
+    );
+    b.to_js_str()
+}
+
+pub fn js_aix_memnew(data: &NmonData) -> String {
+    let Some(measurement) = data.measurement("MEMNEW") else {
+        return js_not_applicable(&ChartsName::AIX_MEMNEW, data);
+    };
+    let series_data = measurement.column_echartjs_vec(&["Process%", "FScache%", "System%", "Free%"]);
+
+    let b = js::AixMemNew::new(
+        data.filename().to_string(),
+        series_data,
+    );
+    b.to_js_str()
+}
+
+pub fn js_aix_memuse(data: &NmonData) -> String {
+    let Some(measurement) = data.measurement("MEMUSE") else {
+        return js_not_applicable(&ChartsName::AIX_MEMUSE, data);
+    };
+    let series_data = measurement.column_echartjs_vec(&["%numperm", "%minperm", "%maxperm", "%numclient", "%maxclient"]);
+
+    let b = js::AixMemUse::new(
+        data.filename().to_string(),
+        series_data,
+    );
+    b.to_js_str()
+}
+
+pub fn js_aix_page(data: &NmonData) -> String {
+    let Some(measurement) = data.measurement("PAGE") else {
+        return js_not_applicable(&ChartsName::AIX_PAGE, data);
+    };
+    let series_data = measurement.column_echartjs_vec(&["faults", "pgin", "pgout", "pgsin", "pgsout"]);
+
+    let b = js::AixPage::new(
+        data.filename().to_string(),
+        series_data,
+    );
+    b.to_js_str()
+}
+
+
+
+
+
+
+
+/// JMeter的TPS、响应时间、错误率与服务器的CPU、内存、磁盘在同一时间轴上对照，
+/// JMeter指标按该nmon文件的快照间隔汇总
+pub fn js_jtl(data: &NmonData, jtl: &Jtl) -> String {
+    let Some(cpu_all) = data.measurement("CPU_ALL") else {
+        let reason = "没有CPU_ALL数据，无法与JMeter结果对照".to_string();
+        return js::NotApplicable::new(&ChartsName::JTL.to_string(), data.filename().to_string(), reason).to_js_str();
+    };
+    let series = jtl.series(&cpu_all.zzzz());
+
+    let mut names = Vec::new();
+    let mut values = vec![series.tps, series.mean, series.p95, series.error];
+    if let Some(idle) = cpu_all.column("Idle%") {
+        names.push("CPU Used%".to_string());
+        values.push(idle.mapv(|idle| 100.0 - idle).to_vec());
+    }
+    if let Some((_, used)) = data.mem_used() {
+        names.push("MEM Used%".to_string());
+        values.push(used.to_vec());
+    }
+    if let Some(diskbusy) = data.measurement("DISKBUSY") {
+        names.push("DISKBUSY Max".to_string());
+        values.push(diskbusy.max_overtime().to_vec());
+    }
+
+    // JMeter指标和服务器资源按相同的时间点降采样
+    let mut points = cpu_all.points_shared(&values).into_iter();
+    let (tps, mean, p95, error) =
+        (points.next().unwrap(), points.next().unwrap(), points.next().unwrap(), points.next().unwrap());
+    let series_data_resource = names.into_iter().zip(points).collect();
+
+    let b = js::JtlCorrelation::new(data.filename().to_string(), tps, mean, p95, error, series_data_resource);
+    b.to_js_str()
+}
+
+/// --chart自定义的图表，指标或匹配的列不存在时显示原因
+pub fn js_chart_spec(data: &NmonData, spec: &ChartSpec) -> String {
+    let chart = ChartsName::SPEC(spec.clone()).to_string();
+    let not_applicable = |reason: String| js::NotApplicable::new(&chart, data.filename().to_string(), reason).to_js_str();
+    let Some(measurement) = data.measurement(spec.section()) else {
+        return not_applicable(format!("该nmon文件没有{}指标", spec.section()));
+    };
+    let columns = spec.columns(measurement);
+    if columns.is_empty() {
+        return not_applicable(format!("{}指标没有匹配的列", spec.section()));
+    }
+    let title = match spec.title() {
+        Some(title) => title,
+        None if measurement.note().is_empty() => measurement.name(),
+        None => measurement.note(),
+    };
+    let unit = spec.unit().unwrap_or_default();
+    match spec.kind() {
+        ChartKind::Line | ChartKind::Area => {
+            let series_data = measurement.column_echartjs_vec(&columns);
+            js::ColumnsOvertime::new(title, unit, data.filename().to_string(), spec.kind() == ChartKind::Area, series_data).to_js_str()
+        }
+        ChartKind::Sum => {
+            let series_data = vec![(columns.join("+"), measurement.columns_sum_echartjs_overtime(&columns))];
+            js::ColumnsOvertime::new(title, unit, data.filename().to_string(), false, series_data).to_js_str()
+        }
+        ChartKind::Awmn => {
+            let index: Vec<usize> = columns
+                .iter()
+                .filter_map(|c| measurement.header().iter().position(|h| h == c))
+                .collect();
+            let pick = |rows: Vec<f32>| index.iter().map(|i| rows[*i]).collect::<Vec<f32>>();
+            let data_avg = pick(measurement.rows_mean());
+            // echarts.js 堆叠柱状图时值不叠加
+            let data_wavg = pick(measurement.rows_wavg()).iter().zip(data_avg.iter()).map(|(wavg, avg)| *wavg - *avg).collect();
+            let title = if unit.is_empty() { title.to_string() } else { format!("{} ({})", title, unit) };
+            js::DiskSummAwmn::new(
+                &title,
+                data.filename().to_string(),
+                columns.iter().map(|c| c.to_string()).collect(),
+                data_avg,
+                data_wavg,
+                pick(measurement.rows_max()),
+                pick(measurement.rows_min()),
+            )
+            .to_js_str()
+        }
+    }
+}
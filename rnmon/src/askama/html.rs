@@ -1,17 +1,49 @@
+use crate::chart::ChartSpec;
+use crate::host::Platform;
+
 use askama::Template;
+use std::str::FromStr;
 
 #[derive(Template)]
 #[template(path = "index_askama.html.jinja2", escape = "none")]
 pub struct NmonHtmlTemplate {
     nmonfiles: Vec<NmonFile>,
     charts: Vec<Chart>,
+    // 多个nmon文件的对比图
+    compares: Vec<Chart>,
+    // watch时每refresh秒重新加载图表数据
+    refresh: Option<u64>,
+    // --single-file时内嵌的echarts库和图表数据
+    inline: Option<InlineScripts>,
+    // 报告定义文件里的报告标题
+    title: Option<String>,
     // nmonjs: Vec<String>,
 }
 impl NmonHtmlTemplate {
-    pub fn new(nmonfiles: Vec<NmonFile>, charts: Vec<Chart>) -> Self {
+    pub fn new(nmonfiles: Vec<NmonFile>, charts: Vec<Chart>, compares: Vec<Chart>, refresh: Option<u64>, inline: Option<InlineScripts>, title: Option<String>) -> Self {
         Self {
             nmonfiles,
             charts,
+            compares,
+            refresh,
+            inline,
+            title,
+        }
+    }
+}
+
+/// 内嵌到HTML里的echarts库和图表数据，compressed为true时data为gzip压缩后的base64
+pub struct InlineScripts {
+    echarts: String,
+    data: String,
+    compressed: bool,
+}
+impl InlineScripts {
+    pub fn new(echarts: String, data: String, compressed: bool) -> Self {
+        Self {
+            echarts,
+            data,
+            compressed,
         }
     }
 }
@@ -20,17 +52,21 @@ pub struct NmonFile {
     id: String,
     name: String,
     selected: String,
+    host: Vec<(String, String)>,
 }
 impl NmonFile {
-    pub fn new(id: String, name: String, selected: String) -> Self {
+    pub fn new(id: String, name: String, selected: String, host: Vec<(String, String)>) -> Self {
         Self {
             id,
             name,
             selected,
+            host,
         }
     }
 }
 
+// 图表名称与命令行、报告定义文件里的名称相同，故不用驼峰命名
+#[allow(non_camel_case_types)]
 #[derive(Clone, Debug)]
 pub enum ChartsName {
     SYS_SUMM,
@@ -41,11 +77,36 @@ pub enum ChartsName {
     MEM_ACTIVE,
     MEM_SWAP,
     DISKBUSY_AWMN,
+    DISKBUSY_OVERTIME,
+    DISK_SUMM,
+    DISKREAD_AWMN,
+    DISKWRITE_AWMN,
+    DISKXFER_AWMN,
+    DISKBSIZE_AWMN,
+    NET_TOTAL,
+    NET_DEVICE,
+    NET_PACKET,
+    TOP_CPU,
+    TOP_RESDATA,
+    VM_PAGING,
+    PROC_RUNQUEUE,
+    PROC_RATES,
+    CMP_CPU,
+    CMP_MEM_FREE,
+    CMP_DISKBUSY,
+    CMP_NET,
+    AIX_LPAR,
+    AIX_MEMNEW,
+    AIX_MEMUSE,
+    AIX_PAGE,
+    JTL,
+    // --chart自定义的图表
+    SPEC(ChartSpec),
 }
 
-impl ToString for ChartsName {
-    fn to_string(&self) -> String {
-        match self {
+impl std::fmt::Display for ChartsName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
             ChartsName::SYS_SUMM => "SYS_SUMM".to_string(),
             ChartsName::CPU_SUMM => "CPU_SUMM".to_string(),
             ChartsName::CPU_ALL => "CPU_ALL".to_string(),
@@ -54,32 +115,180 @@ impl ToString for ChartsName {
             ChartsName::MEM_ACTIVE => "MEM_ACTIVE".to_string(),
             ChartsName::MEM_SWAP => "MEM_SWAP".to_string(),
             ChartsName::DISKBUSY_AWMN => "DISKBUSY_AWMN".to_string(),
+            ChartsName::DISKBUSY_OVERTIME => "DISKBUSY_OVERTIME".to_string(),
+            ChartsName::DISK_SUMM => "DISK_SUMM".to_string(),
+            ChartsName::DISKREAD_AWMN => "DISKREAD_AWMN".to_string(),
+            ChartsName::DISKWRITE_AWMN => "DISKWRITE_AWMN".to_string(),
+            ChartsName::DISKXFER_AWMN => "DISKXFER_AWMN".to_string(),
+            ChartsName::DISKBSIZE_AWMN => "DISKBSIZE_AWMN".to_string(),
+            ChartsName::NET_TOTAL => "NET_TOTAL".to_string(),
+            ChartsName::NET_DEVICE => "NET_DEVICE".to_string(),
+            ChartsName::NET_PACKET => "NET_PACKET".to_string(),
+            ChartsName::TOP_CPU => "TOP_CPU".to_string(),
+            ChartsName::TOP_RESDATA => "TOP_RESDATA".to_string(),
+            ChartsName::VM_PAGING => "VM_PAGING".to_string(),
+            ChartsName::PROC_RUNQUEUE => "PROC_RUNQUEUE".to_string(),
+            ChartsName::PROC_RATES => "PROC_RATES".to_string(),
+            ChartsName::CMP_CPU => "CMP_CPU".to_string(),
+            ChartsName::CMP_MEM_FREE => "CMP_MEM_FREE".to_string(),
+            ChartsName::CMP_DISKBUSY => "CMP_DISKBUSY".to_string(),
+            ChartsName::CMP_NET => "CMP_NET".to_string(),
+            ChartsName::AIX_LPAR => "AIX_LPAR".to_string(),
+            ChartsName::AIX_MEMNEW => "AIX_MEMNEW".to_string(),
+            ChartsName::AIX_MEMUSE => "AIX_MEMUSE".to_string(),
+            ChartsName::AIX_PAGE => "AIX_PAGE".to_string(),
+            ChartsName::JTL => "JTL".to_string(),
+            ChartsName::SPEC(spec) => spec.to_string(),
+        };
+        f.write_str(&name)
+    }
+
+}
+
+impl FromStr for ChartsName {
+    type Err = String;
+
+    /// 内置图表的名称，比如: SYS_SUMM、DISKBUSY_AWMN
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ChartsName::builtin()
+            .into_iter()
+            .find(|name| name.to_string() == s)
+            .ok_or_else(|| format!("图表'{}'不是内置图表，内置图表有: {}", s, ChartsName::builtin().iter().map(|n| n.to_string()).collect::<Vec<_>>().join("、")))
+    }
+}
+
+impl ChartsName {
+    /// 图表元素<div>的id及图表数据的key里的名称，内置图表为图表名称。
+    /// 自定义图表的SPEC里有:、,、*、引号等字符，除字母、数字外的字符都转换为_XX(十六进制)，不同的SPEC不会相同
+    pub fn id(&self) -> String {
+        match self {
+            ChartsName::SPEC(spec) => {
+                let mut id = String::from("SPEC_");
+                for b in spec.to_string().bytes() {
+                    if b.is_ascii_alphanumeric() {
+                        id.push(b as char);
+                    } else {
+                        id.push_str(&format!("_{:02X}", b));
+                    }
+                }
+                id
+            },
+            _ => self.to_string(),
         }
     }
 
+    /// 所有内置图表，不包括--chart自定义的图表
+    pub fn builtin() -> Vec<ChartsName> {
+        vec![
+            ChartsName::SYS_SUMM,
+            ChartsName::CPU_SUMM,
+            ChartsName::CPU_ALL,
+            ChartsName::JFSFILE,
+            ChartsName::MEM_FREE,
+            ChartsName::MEM_ACTIVE,
+            ChartsName::MEM_SWAP,
+            ChartsName::DISKBUSY_AWMN,
+            ChartsName::DISKBUSY_OVERTIME,
+            ChartsName::DISK_SUMM,
+            ChartsName::DISKREAD_AWMN,
+            ChartsName::DISKWRITE_AWMN,
+            ChartsName::DISKXFER_AWMN,
+            ChartsName::DISKBSIZE_AWMN,
+            ChartsName::NET_TOTAL,
+            ChartsName::NET_DEVICE,
+            ChartsName::NET_PACKET,
+            ChartsName::TOP_CPU,
+            ChartsName::TOP_RESDATA,
+            ChartsName::VM_PAGING,
+            ChartsName::PROC_RUNQUEUE,
+            ChartsName::PROC_RATES,
+            ChartsName::CMP_CPU,
+            ChartsName::CMP_MEM_FREE,
+            ChartsName::CMP_DISKBUSY,
+            ChartsName::CMP_NET,
+            ChartsName::AIX_LPAR,
+            ChartsName::AIX_MEMNEW,
+            ChartsName::AIX_MEMUSE,
+            ChartsName::AIX_PAGE,
+            ChartsName::JTL,
+        ]
+    }
+
+    /// 多个nmon文件的对比图
+    pub fn is_compare(&self) -> bool {
+        matches!(self, ChartsName::CMP_CPU | ChartsName::CMP_MEM_FREE | ChartsName::CMP_DISKBUSY | ChartsName::CMP_NET)
+    }
+
+    /// 生成该图表需要解析的指标
+    pub fn sections(&self) -> Vec<String> {
+        let sections: &[&str] = match self {
+            ChartsName::SYS_SUMM => &["CPU_ALL", "DISKXFER"],
+            ChartsName::CPU_SUMM => &[crate::section::CPU_SECTIONS],
+            ChartsName::CPU_ALL | ChartsName::CMP_CPU => &["CPU_ALL"],
+            ChartsName::JFSFILE => &["JFSFILE"],
+            ChartsName::MEM_FREE | ChartsName::MEM_ACTIVE | ChartsName::MEM_SWAP | ChartsName::CMP_MEM_FREE => &["MEM"],
+            ChartsName::DISKBUSY_AWMN | ChartsName::DISKBUSY_OVERTIME | ChartsName::CMP_DISKBUSY => &["DISKBUSY"],
+            ChartsName::DISK_SUMM => &["DISKREAD", "DISKWRITE", "DISKXFER"],
+            ChartsName::DISKREAD_AWMN => &["DISKREAD"],
+            ChartsName::DISKWRITE_AWMN => &["DISKWRITE"],
+            ChartsName::DISKXFER_AWMN => &["DISKXFER"],
+            ChartsName::DISKBSIZE_AWMN => &["DISKBSIZE"],
+            ChartsName::NET_TOTAL | ChartsName::NET_DEVICE | ChartsName::CMP_NET => &["NET"],
+            ChartsName::NET_PACKET => &["NETPACKET"],
+            ChartsName::TOP_CPU | ChartsName::TOP_RESDATA => &["TOP", "UARG"],
+            ChartsName::VM_PAGING => &["VM"],
+            ChartsName::PROC_RUNQUEUE | ChartsName::PROC_RATES => &["PROC", "CPU_ALL"],
+            ChartsName::AIX_LPAR => &["LPAR"],
+            ChartsName::AIX_MEMNEW => &["MEMNEW"],
+            ChartsName::AIX_MEMUSE => &["MEMUSE"],
+            ChartsName::AIX_PAGE => &["PAGE"],
+            ChartsName::JTL => &["CPU_ALL", "MEM", "MEMNEW", "DISKBUSY"],
+            ChartsName::SPEC(spec) => return vec![spec.section().to_string()],
+        };
+        sections.iter().map(|s| s.to_string()).collect()
+    }
+
+    /// 只适用于某个平台的图表，None表示各平台通用
+    pub fn platform(&self) -> Option<Platform> {
+        match self {
+            ChartsName::MEM_FREE | ChartsName::MEM_ACTIVE | ChartsName::MEM_SWAP | ChartsName::VM_PAGING => Some(Platform::Linux),
+            ChartsName::AIX_LPAR | ChartsName::AIX_MEMNEW | ChartsName::AIX_MEMUSE | ChartsName::AIX_PAGE => Some(Platform::Aix),
+            _ => None,
+        }
+    }
 }
 
 pub struct Chart {
     name: String,
+    // 选择列表里显示的名称，报告定义文件里指定了标题时为标题，自定义图表为SPEC
+    label: String,
     selected: String,
 }
 impl Chart {
     pub fn new(name: &ChartsName, selected: bool) -> Self {
+        let label = match name {
+            ChartsName::SPEC(spec) => spec.title().map(|t| t.to_string()).unwrap_or_else(|| name.to_string()),
+            _ => name.to_string(),
+        };
         if selected {
             Self {
-                name: name.to_string(),
+                name: name.id(),
+                label,
                 selected: "selected".to_string(),
             }
         } else {
             Self {
-                name: name.to_string(),
+                name: name.id(),
+                label,
                 selected: "".to_string(),
             }
         }
     }
 
-    pub fn name(&self) -> &str {
-        &self.name
+    /// 选择列表里显示的名称改为报告定义文件里指定的标题
+    pub fn with_label(mut self, label: &str) -> Self {
+        self.label = label.to_string();
+        self
     }
 
 }
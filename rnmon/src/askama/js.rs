@@ -7,10 +7,21 @@ use super::ToJStr;
 pub struct EchartsOption {
     id: String,
     option: String,
+    // 覆盖图表的标题和Y轴的单位，为JSON字符串
+    title: Option<String>,
+    unit: Option<String>,
 }
 impl EchartsOption {
     pub fn new(id: String, option: String) -> Self {
-        Self { id, option }
+        Self { id, option, title: None, unit: None }
+    }
+
+    /// 报告定义文件里内置图表指定的标题和单位
+    pub fn with_label(mut self, title: Option<String>, unit: Option<String>) -> Self {
+        let json = |s: String| serde_json::to_string(&s).unwrap_or_default();
+        self.title = title.map(json);
+        self.unit = unit.map(json);
+        self
     }
 }
 
@@ -179,72 +190,73 @@ impl ToJStr for CpuAll {
 
 
 
-// #[derive(Template)]
-// #[template(path = "options/options_disk_summ_awmn.js.jinja2")]
-// pub struct DiskSummAwmn {
-//     nmon_name: String,
-//     axis_label: Vec<String>,
-//     data_avg: Vec<f32>,
-//     data_wavg: Vec<f32>,
-//     data_max: Vec<f32>,
-//     data_min: Vec<f32>,
-// }
+#[derive(Template)]
+#[template(path = "options/options_disk_summ_awmn.js.jinja2")]
+pub struct DiskSummAwmn {
+    nmon_name: String,
+    axis_label: Vec<String>,
+    data_avg: Vec<f32>,
+    data_wavg: Vec<f32>,
+    data_max: Vec<f32>,
+    data_min: Vec<f32>,
+}
 
-// impl DiskSummAwmn {
-//     pub fn new(
-//         nmon_name: String,
-//         axis_label: Vec<String>,
-//         data_avg: Vec<f32>,
-//         data_wavg: Vec<f32>,
-//         data_max: Vec<f32>,
-//         data_min: Vec<f32>,
-//     ) -> Self {
-//         Self {
-//             nmon_name: format!("Disk %Busy {}", nmon_name),
-//             axis_label,
-//             data_avg,
-//             data_wavg,
-//             data_max,
-//             data_min,
-//         }
-//     }
-// }
+impl DiskSummAwmn {
+    pub fn new(
+        title: &str,
+        nmon_name: String,
+        axis_label: Vec<String>,
+        data_avg: Vec<f32>,
+        data_wavg: Vec<f32>,
+        data_max: Vec<f32>,
+        data_min: Vec<f32>,
+    ) -> Self {
+        Self {
+            nmon_name: format!("{} {}", title, nmon_name),
+            axis_label,
+            data_avg,
+            data_wavg,
+            data_max,
+            data_min,
+        }
+    }
+}
 
-// impl ToJStr for DiskSummAwmn {
-// }
+impl ToJStr for DiskSummAwmn {
+}
 
 
 
 
 
 
-// #[derive(Template)]
-// #[template(path = "options/options_disk_summ_overtime.js.jinja2")]
-// pub struct DiskSummOvertime {
-//     nmon_name: String,
-//     series_data_read: Vec<Point>,
-//     series_data_write: Vec<Point>,
-//     series_data_io: Vec<Point>,
-// }
+#[derive(Template)]
+#[template(path = "options/options_disk_summ_overtime.js.jinja2")]
+pub struct DiskSummOvertime {
+    nmon_name: String,
+    series_data_read: Vec<Point>,
+    series_data_write: Vec<Point>,
+    series_data_io: Vec<Point>,
+}
 
-// impl DiskSummOvertime {
-//     pub fn new(
-//         nmon_name: String,
-//         series_data_read: Vec<Point>,
-//         series_data_write: Vec<Point>,
-//         series_data_io: Vec<Point>,
-//     ) -> Self {
-//         Self {
-//             nmon_name: format!("Disk total KB/s Overtime {}", nmon_name),
-//             series_data_read,
-//             series_data_write,
-//             series_data_io,
-//         }
-//     }
-// }
+impl DiskSummOvertime {
+    pub fn new(
+        nmon_name: String,
+        series_data_read: Vec<Point>,
+        series_data_write: Vec<Point>,
+        series_data_io: Vec<Point>,
+    ) -> Self {
+        Self {
+            nmon_name: format!("Disk total KB/s Overtime {}", nmon_name),
+            series_data_read,
+            series_data_write,
+            series_data_io,
+        }
+    }
+}
 
-// impl ToJStr for DiskSummOvertime {
-// }
+impl ToJStr for DiskSummOvertime {
+}
 
 
 
@@ -307,64 +319,94 @@ impl ToJStr for DiskBusyAwmn {
 
 
 
-// #[derive(Template)]
-// #[template(path = "options/options_diskbusy_overtime.js.jinja2")]
-// pub struct DiskBusyOvertime {
-//     nmon_name: String,
-//     series_data: Vec<(String, Vec<Point>)>,
-// }
+#[derive(Template)]
+#[template(path = "options/options_diskbusy_overtime.js.jinja2")]
+pub struct DiskBusyOvertime {
+    nmon_name: String,
+    series_data: Vec<(String, Vec<Point>)>,
+}
+
+
+impl DiskBusyOvertime {
+    pub fn new(
+        nmon_name: String,
+        series_data: Vec<(String, Vec<Point>)>,
+    ) -> Self {
+        Self {
+            nmon_name: format!("Disk %Busy over time {}", nmon_name),
+            series_data,
+        }
+    }
+}
+
+impl ToJStr for DiskBusyOvertime {
+}
+
+
+
+
 
 
-// impl DiskBusyOvertime {
-//     pub fn new(
-//         nmon_name: String,
-//         series_data: Vec<(String, Vec<Point>)>,
-//     ) -> Self {
-//         Self {
-//             nmon_name: format!("Disk %Busy over time {}", nmon_name),
-//             series_data,
-//         }
-//     }
-// }
 
-// impl ToJStr for DiskBusyOvertime {
-// }
 
 
 
+#[derive(Template)]
+#[template(path = "options/options_network_io_total.js.jinja2")]
+pub struct NetworkIOTotal {
+    nmon_name: String,
+    series_data_read: Vec<Point>,
+    series_data_write_ve: Vec<Point>,
+}
 
+impl NetworkIOTotal {
+    pub fn new(
+        nmon_name: String,
+        series_data_read: Vec<Point>,
+        series_data_write_ve: Vec<Point>,
+    ) -> Self {
+        Self {
+            nmon_name: format!("Network I/O Total by Overtime {}", nmon_name),
+            series_data_read,
+            series_data_write_ve,
+        }
+    }
+}
 
+impl ToJStr for NetworkIOTotal {
+}
 
 
 
 
 
-// #[derive(Template)]
-// #[template(path = "options/options_network_io_total.js.jinja2")]
-// pub struct NetworkIOTotal {
-//     nmon_name: String,
-//     series_data_read: Vec<Point>,
-//     series_data_write_ve: Vec<Point>,
-// }
 
-// impl NetworkIOTotal {
-//     pub fn new(
-//         nmon_name: String,
-//         series_data_read: Vec<Point>,
-//         series_data_write_ve: Vec<Point>,
-//     ) -> Self {
-//         Self {
-//             nmon_name: format!("Network I/O Total by Overtime {}", nmon_name),
-//             series_data_read,
-//             series_data_write_ve,
-//         }
-//     }
-// }
 
-// impl ToJStr for NetworkIOTotal {
-// }
 
 
+#[derive(Template)]
+#[template(path = "options/options_network_io_device.js.jinja2")]
+pub struct NetworkIODevice {
+    nmon_name: String,
+    series_data: Vec<(String, Vec<Point>)>,
+}
+
+
+impl NetworkIODevice {
+    pub fn new(
+        nmon_name: String,
+        series_data: Vec<(String, Vec<Point>)>,
+    ) -> Self {
+        Self {
+            nmon_name: format!("Network I/O By Device {}", nmon_name),
+            series_data,
+        }
+    }
+}
+
+impl ToJStr for NetworkIODevice {
+}
+
 
 
 
@@ -372,28 +414,29 @@ impl ToJStr for DiskBusyAwmn {
 
 
 
-// #[derive(Template)]
-// #[template(path = "options/options_network_io_device.js.jinja2")]
-// pub struct NetworkIODevice {
-//     nmon_name: String,
-//     series_data: Vec<(String, Vec<Point>)>,
-// }
+
+#[derive(Template)]
+#[template(path = "options/options_network_packet.js.jinja2")]
+pub struct NetworkPacket {
+    nmon_name: String,
+    series_data: Vec<(String, Vec<Point>)>,
+}
 
 
-// impl NetworkIODevice {
-//     pub fn new(
-//         nmon_name: String,
-//         series_data: Vec<(String, Vec<Point>)>,
-//     ) -> Self {
-//         Self {
-//             nmon_name: format!("Network I/O By Device {}", nmon_name),
-//             series_data,
-//         }
-//     }
-// }
+impl NetworkPacket {
+    pub fn new(
+        nmon_name: String,
+        series_data: Vec<(String, Vec<Point>)>,
+    ) -> Self {
+        Self {
+            nmon_name: format!("Network Packets/s {}", nmon_name),
+            series_data,
+        }
+    }
+}
 
-// impl ToJStr for NetworkIODevice {
-// }
+impl ToJStr for NetworkPacket {
+}
 
 
 
@@ -541,3 +584,395 @@ impl MemActive {
 
 impl ToJStr for MemActive {
 }
+
+
+
+
+
+
+
+
+
+
+#[derive(Template)]
+#[template(path = "options/options_top_process.js.jinja2")]
+pub struct TopProcess {
+    nmon_name: String,
+    y_name: String,
+    series_data: Vec<(String, Vec<Point>)>,
+}
+
+impl TopProcess {
+    pub fn new(
+        title: &str,
+        y_name: &str,
+        nmon_name: String,
+        series_data: Vec<(String, Vec<Point>)>,
+    ) -> Self {
+        Self {
+            nmon_name: format!("{} {}", title, nmon_name),
+            y_name: y_name.to_string(),
+            series_data,
+        }
+    }
+}
+
+impl ToJStr for TopProcess {
+}
+
+
+
+
+
+
+
+
+
+
+#[derive(Template)]
+#[template(path = "options/options_vm_paging.js.jinja2")]
+pub struct VmPaging {
+    nmon_name: String,
+    series_data: Vec<(String, Vec<Point>)>,
+}
+
+impl VmPaging {
+    pub fn new(
+        nmon_name: String,
+        series_data: Vec<(String, Vec<Point>)>,
+    ) -> Self {
+        Self {
+            nmon_name: format!("Paging and Virtual Memory {}", nmon_name),
+            series_data,
+        }
+    }
+}
+
+impl ToJStr for VmPaging {
+}
+
+
+
+
+
+
+
+
+
+#[derive(Template)]
+#[template(path = "options/options_proc_runqueue.js.jinja2")]
+pub struct ProcRunQueue {
+    nmon_name: String,
+    series_data: Vec<(String, Vec<Point>)>,
+    series_data_cpus: Vec<Point>,
+}
+
+impl ProcRunQueue {
+    pub fn new(
+        nmon_name: String,
+        series_data: Vec<(String, Vec<Point>)>,
+        series_data_cpus: Vec<Point>,
+    ) -> Self {
+        Self {
+            nmon_name: format!("Run Queue vs CPUs {}", nmon_name),
+            series_data,
+            series_data_cpus,
+        }
+    }
+}
+
+impl ToJStr for ProcRunQueue {
+}
+
+
+
+
+
+
+
+
+
+#[derive(Template)]
+#[template(path = "options/options_proc_rates.js.jinja2")]
+pub struct ProcRates {
+    nmon_name: String,
+    series_data: Vec<(String, Vec<Point>)>,
+    series_data_fork: Vec<Point>,
+}
+
+impl ProcRates {
+    pub fn new(
+        nmon_name: String,
+        series_data: Vec<(String, Vec<Point>)>,
+        series_data_fork: Vec<Point>,
+    ) -> Self {
+        Self {
+            nmon_name: format!("Process Switch/Syscall/Fork {}", nmon_name),
+            series_data,
+            series_data_fork,
+        }
+    }
+}
+
+impl ToJStr for ProcRates {
+}
+
+
+
+
+
+
+
+
+/// 多个nmon文件(服务器)同一指标的对比图，每个服务器一条线
+#[derive(Template)]
+#[template(path = "options/options_compare.js.jinja2")]
+pub struct Compare {
+    title: String,
+    y_name: String,
+    series_data: Vec<(String, Vec<Point>)>,
+}
+
+impl Compare {
+    pub fn new(
+        title: &str,
+        y_name: &str,
+        series_data: Vec<(String, Vec<Point>)>,
+    ) -> Self {
+        Self {
+            title: format!("Compare {}", title),
+            y_name: y_name.to_string(),
+            series_data,
+        }
+    }
+}
+
+impl ToJStr for Compare {
+}
+
+
+
+
+
+
+
+
+/// 图表不适用于该nmon文件时的占位，比如AIX的nmon文件没有Linux的MEM列
+#[derive(Template)]
+#[template(path = "options/options_not_applicable.js.jinja2")]
+pub struct NotApplicable {
+    nmon_name: String,
+    reason: String,
+}
+
+impl NotApplicable {
+    pub fn new(
+        chart: &str,
+        nmon_name: String,
+        reason: String,
+    ) -> Self {
+        Self {
+            nmon_name: format!("{} {}", chart, nmon_name),
+            reason,
+        }
+    }
+}
+
+impl ToJStr for NotApplicable {
+}
+
+
+
+
+
+
+
+
+#[derive(Template)]
+#[template(path = "options/options_aix_lpar.js.jinja2")]
+pub struct AixLpar {
+    nmon_name: String,
+    series_data: Vec<(String, Vec<Point>)>,
+    series_data_ec: Vec<Point>,
+}
+
+impl AixLpar {
+    pub fn new(
+        nmon_name: String,
+        series_data: Vec<(String, Vec<Point>)>,
+        series_data_ec: Vec<Point>,
+    ) -> Self {
+        Self {
+            nmon_name: format!("LPAR Physical CPU vs Entitlement {}", nmon_name),
+            series_data,
+            series_data_ec,
+        }
+    }
+}
+
+impl ToJStr for AixLpar {
+}
+
+
+
+
+
+
+
+
+#[derive(Template)]
+#[template(path = "options/options_aix_memnew.js.jinja2")]
+pub struct AixMemNew {
+    nmon_name: String,
+    series_data: Vec<(String, Vec<Point>)>,
+}
+
+impl AixMemNew {
+    pub fn new(
+        nmon_name: String,
+        series_data: Vec<(String, Vec<Point>)>,
+    ) -> Self {
+        Self {
+            nmon_name: format!("Memory Use % (MEMNEW) {}", nmon_name),
+            series_data,
+        }
+    }
+}
+
+impl ToJStr for AixMemNew {
+}
+
+
+
+
+
+
+
+
+#[derive(Template)]
+#[template(path = "options/options_aix_memuse.js.jinja2")]
+pub struct AixMemUse {
+    nmon_name: String,
+    series_data: Vec<(String, Vec<Point>)>,
+}
+
+impl AixMemUse {
+    pub fn new(
+        nmon_name: String,
+        series_data: Vec<(String, Vec<Point>)>,
+    ) -> Self {
+        Self {
+            nmon_name: format!("Memory numperm/numclient (MEMUSE) {}", nmon_name),
+            series_data,
+        }
+    }
+}
+
+impl ToJStr for AixMemUse {
+}
+
+
+
+
+
+
+
+
+#[derive(Template)]
+#[template(path = "options/options_aix_page.js.jinja2")]
+pub struct AixPage {
+    nmon_name: String,
+    series_data: Vec<(String, Vec<Point>)>,
+}
+
+impl AixPage {
+    pub fn new(
+        nmon_name: String,
+        series_data: Vec<(String, Vec<Point>)>,
+    ) -> Self {
+        Self {
+            nmon_name: format!("Paging (PAGE) {}", nmon_name),
+            series_data,
+        }
+    }
+}
+
+impl ToJStr for AixPage {
+}
+
+
+
+
+
+
+
+#[derive(Template)]
+#[template(path = "options/options_jtl.js.jinja2")]
+pub struct JtlCorrelation {
+    nmon_name: String,
+    series_data_tps: Vec<Point>,
+    series_data_mean: Vec<Point>,
+    series_data_p95: Vec<Point>,
+    series_data_error: Vec<Point>,
+    // 服务器资源: CPU Used%、MEM Used%、DISKBUSY Max
+    series_data_resource: Vec<(String, Vec<Point>)>,
+}
+
+impl JtlCorrelation {
+    pub fn new(
+        nmon_name: String,
+        series_data_tps: Vec<Point>,
+        series_data_mean: Vec<Point>,
+        series_data_p95: Vec<Point>,
+        series_data_error: Vec<Point>,
+        series_data_resource: Vec<(String, Vec<Point>)>,
+    ) -> Self {
+        Self {
+            nmon_name: format!("JMeter vs {}", nmon_name),
+            series_data_tps,
+            series_data_mean,
+            series_data_p95,
+            series_data_error,
+            series_data_resource,
+        }
+    }
+}
+
+impl ToJStr for JtlCorrelation {
+}
+
+
+
+
+
+
+
+#[derive(Template)]
+#[template(path = "options/options_columns_overtime.js.jinja2")]
+pub struct ColumnsOvertime {
+    nmon_name: String,
+    y_name: String,
+    // 堆叠面积图
+    stacked: bool,
+    series_data: Vec<(String, Vec<Point>)>,
+}
+
+impl ColumnsOvertime {
+    pub fn new(
+        title: &str,
+        y_name: &str,
+        nmon_name: String,
+        stacked: bool,
+        series_data: Vec<(String, Vec<Point>)>,
+    ) -> Self {
+        Self {
+            nmon_name: format!("{} {}", title, nmon_name),
+            y_name: y_name.to_string(),
+            stacked,
+            series_data,
+        }
+    }
+}
+
+impl ToJStr for ColumnsOvertime {
+}
@@ -0,0 +1,340 @@
+use anyhow::Result;
+use anyhow::anyhow;
+use askama::Template;
+use log::debug;
+use log::warn;
+use ndarray::Array1;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::NmonArgs;
+use crate::NmonData;
+use crate::RunNmonArgs;
+use crate::TimeBound;
+use crate::askama::compare_names;
+use crate::stats;
+
+/// 有回归时rnmon compare的退出码，与阈值规则不通过(3)区分
+pub const REGRESSION_EXIT_CODE: i32 = 4;
+
+/// 判断回归的容差
+#[derive(Debug, Clone)]
+pub struct Tolerance {
+    /// 吞吐量下降超过该百分比
+    pub throughput: f64,
+    /// 平均、p90、p95、p99响应时间上升超过该百分比
+    pub response_time: f64,
+    /// 错误率上升超过该百分点
+    pub error: f64,
+    /// CPU、内存、DISKBUSY使用率上升超过该百分点
+    pub resource: f64,
+}
+
+/// 对比的指标，JMeter的p90、p95、p99为statistics.json里的pct1ResTime、pct2ResTime、pct3ResTime(JMeter的默认设置)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Metric {
+    Throughput,
+    Mean,
+    P90,
+    P95,
+    P99,
+    Error,
+    CpuMean,
+    CpuMax,
+    MemMean,
+    MemMax,
+    DiskBusyMean,
+    DiskBusyMax,
+}
+
+impl Metric {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Metric::Throughput => "throughput(/s)",
+            Metric::Mean => "mean(ms)",
+            Metric::P90 => "p90(ms)",
+            Metric::P95 => "p95(ms)",
+            Metric::P99 => "p99(ms)",
+            Metric::Error => "error%",
+            Metric::CpuMean => "CPU Used% mean",
+            Metric::CpuMax => "CPU Used% max",
+            Metric::MemMean => "MEM Used% mean",
+            Metric::MemMax => "MEM Used% max",
+            Metric::DiskBusyMean => "DISKBUSY Max mean",
+            Metric::DiskBusyMax => "DISKBUSY Max max",
+        }
+    }
+
+    /// 吞吐量和响应时间按变化的百分比判断，错误率和资源使用率按变化的百分点判断
+    fn relative(&self) -> bool {
+        matches!(self, Metric::Throughput | Metric::Mean | Metric::P90 | Metric::P95 | Metric::P99)
+    }
+
+    /// 变化量及是否回归
+    fn change(&self, baseline: f64, candidate: f64, tolerance: &Tolerance) -> (Option<f64>, bool) {
+        let change = if self.relative() {
+            if baseline == 0. {
+                return (None, false);
+            }
+            (candidate - baseline) / baseline * 100.
+        } else {
+            candidate - baseline
+        };
+        let regression = match self {
+            Metric::Throughput => change < -tolerance.throughput,
+            Metric::Mean | Metric::P90 | Metric::P95 | Metric::P99 => change > tolerance.response_time,
+            Metric::Error => change > tolerance.error,
+            _ => change > tolerance.resource,
+        };
+        (Some(change), regression)
+    }
+}
+
+/// 一次测试的结果目录里JMeter各事务的统计值，以及各服务器的资源使用
+#[derive(Debug, Clone, Default)]
+pub struct RunSummary {
+    path: PathBuf,
+    transactions: BTreeMap<String, BTreeMap<Metric, f64>>,
+    hosts: BTreeMap<String, BTreeMap<Metric, f64>>,
+}
+
+impl RunSummary {
+    /// 读取rmeter的测试结果目录: res/statistics.json为JMeter报告的统计值，nmon目录(没有时为结果目录本身)里为nmon文件。
+    /// from、to同rnmon的--from、--to，只统计该时间段内的资源使用
+    pub fn read(dir: &Path, from: Option<TimeBound>, to: Option<TimeBound>, lenient: bool) -> Result<Self> {
+        if !dir.is_dir() {
+            return Err(anyhow!("{}不是测试结果目录", dir.display()));
+        }
+        let mut summary = Self { path: dir.to_path_buf(), ..Default::default() };
+
+        let statistics = dir.join("res").join("statistics.json");
+        if statistics.is_file() {
+            summary.transactions = read_statistics(&statistics)?;
+        } else {
+            warn!("{}不存在，不对比JMeter的统计值", statistics.display());
+        }
+
+        let nmon_dir = dir.join("nmon");
+        let nmon_dir = if nmon_dir.is_dir() { nmon_dir } else { dir.to_path_buf() };
+        let run_nmon_args = RunNmonArgs { from, to, lenient, ..Default::default() };
+        let nmon_args = NmonArgs::new(vec![nmon_dir], dir.to_path_buf(), run_nmon_args);
+        let cell: HashSet<String> = ["CPU_ALL", "MEM", "MEMNEW", "DISKBUSY"].iter().map(|s| s.to_string()).collect();
+        let (nmon_datas, _) = nmon_args.nmon_dates(&cell)?;
+        for (ndata, name) in nmon_datas.iter().zip(compare_names(&nmon_datas)) {
+            summary.hosts.insert(name, host_stats(ndata));
+        }
+        debug!("{}共{}个事务、{}个服务器", dir.display(), summary.transactions.len(), summary.hosts.len());
+        Ok(summary)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// JMeter报告的statistics.json，每个事务(包括Total)一个对象
+fn read_statistics(path: &Path) -> Result<BTreeMap<String, BTreeMap<Metric, f64>>> {
+    let content = std::fs::read_to_string(path).map_err(|e| anyhow!("{}读取失败: {}", path.display(), e))?;
+    let json: Value = serde_json::from_str(&content).map_err(|e| anyhow!("{}解析失败: {}", path.display(), e))?;
+    let Value::Object(transactions) = json else {
+        return Err(anyhow!("{}不是JMeter报告的statistics.json", path.display()));
+    };
+    let mut res = BTreeMap::new();
+    for (name, stats) in transactions.iter() {
+        let mut metrics = BTreeMap::new();
+        for (metric, key) in [
+            (Metric::Throughput, "throughput"),
+            (Metric::Mean, "meanResTime"),
+            (Metric::P90, "pct1ResTime"),
+            (Metric::P95, "pct2ResTime"),
+            (Metric::P99, "pct3ResTime"),
+            (Metric::Error, "errorPct"),
+        ] {
+            if let Some(value) = stats.get(key).and_then(|v| v.as_f64()) {
+                metrics.insert(metric, value);
+            }
+        }
+        res.insert(name.clone(), metrics);
+    }
+    Ok(res)
+}
+
+fn host_stats(ndata: &NmonData) -> BTreeMap<Metric, f64> {
+    let mut metrics = BTreeMap::new();
+    let mut insert = |mean: Metric, max: Metric, array1: Option<Array1<f32>>| {
+        if let Some(array1) = array1.filter(|a| !a.is_empty()) {
+            let values = array1.to_vec();
+            metrics.insert(mean, stats::mean(&values) as f64);
+            metrics.insert(max, stats::max(&values) as f64);
+        }
+    };
+    let cpu_used = ndata.measurement("CPU_ALL").and_then(|m| m.column("Idle%")).map(|idle| idle.mapv(|idle| 100.0 - idle));
+    insert(Metric::CpuMean, Metric::CpuMax, cpu_used);
+    insert(Metric::MemMean, Metric::MemMax, ndata.mem_used().map(|(_, used)| used));
+    insert(Metric::DiskBusyMean, Metric::DiskBusyMax, ndata.measurement("DISKBUSY").map(|m| m.max_overtime()));
+    metrics
+}
+
+/// 对比结果的一行
+#[derive(Debug, Clone)]
+pub struct DiffRow {
+    /// JMeter或nmon
+    pub source: &'static str,
+    /// 事务名称或服务器名称
+    pub name: String,
+    pub metric: Metric,
+    pub baseline: Option<f64>,
+    pub candidate: Option<f64>,
+    /// 吞吐量和响应时间为变化的百分比，错误率和资源使用率为变化的百分点
+    pub change: Option<f64>,
+    pub regression: bool,
+}
+
+impl DiffRow {
+    pub fn baseline_text(&self) -> String {
+        self.baseline.map(|v| format!("{:.2}", v)).unwrap_or_else(|| "-".to_string())
+    }
+    pub fn candidate_text(&self) -> String {
+        self.candidate.map(|v| format!("{:.2}", v)).unwrap_or_else(|| "-".to_string())
+    }
+    pub fn change_text(&self) -> String {
+        match self.change {
+            Some(change) if self.metric.relative() => format!("{:+.2}%", change),
+            Some(change) => format!("{:+.2}pp", change),
+            None => "-".to_string(),
+        }
+    }
+    pub fn result_text(&self) -> &'static str {
+        match (self.regression, self.change) {
+            (true, _) => "REGRESSION",
+            (false, Some(_)) => "OK",
+            (false, None) => "-",
+        }
+    }
+}
+
+/// 对比两次测试，只在一次测试里有的事务或服务器也列出，但不判断回归。Total事务排在最前
+pub fn compare(baseline: &RunSummary, candidate: &RunSummary, tolerance: &Tolerance) -> Vec<DiffRow> {
+    let mut rows = Vec::new();
+    let groups = [("JMeter", &baseline.transactions, &candidate.transactions), ("nmon", &baseline.hosts, &candidate.hosts)];
+    for (source, a, b) in groups {
+        let mut names: Vec<&String> = a.keys().chain(b.keys().filter(|k| !a.contains_key(*k))).collect();
+        names.sort_by_key(|name| (name.as_str() != "Total", name.to_string()));
+        for name in names {
+            let empty = BTreeMap::new();
+            let (ma, mb) = (a.get(name).unwrap_or(&empty), b.get(name).unwrap_or(&empty));
+            let mut metrics: Vec<&Metric> = ma.keys().chain(mb.keys()).collect();
+            metrics.sort();
+            metrics.dedup();
+            for metric in metrics {
+                let (va, vb) = (ma.get(metric).copied(), mb.get(metric).copied());
+                let (change, regression) = match (va, vb) {
+                    (Some(va), Some(vb)) => metric.change(va, vb, tolerance),
+                    _ => (None, false),
+                };
+                rows.push(DiffRow {
+                    source,
+                    name: name.clone(),
+                    metric: *metric,
+                    baseline: va,
+                    candidate: vb,
+                    change,
+                    regression,
+                });
+            }
+        }
+    }
+    rows
+}
+
+#[derive(Template)]
+#[template(path = "compare_report.html.jinja2")]
+struct CompareReport<'a> {
+    baseline: String,
+    candidate: String,
+    tolerance: &'a Tolerance,
+    regressions: usize,
+    rows: &'a [DiffRow],
+}
+
+/// HTML格式的对比报告，回归的行标红
+pub fn html(baseline: &RunSummary, candidate: &RunSummary, tolerance: &Tolerance, rows: &[DiffRow]) -> String {
+    CompareReport {
+        baseline: baseline.path().display().to_string(),
+        candidate: candidate.path().display().to_string(),
+        tolerance,
+        regressions: rows.iter().filter(|r| r.regression).count(),
+        rows,
+    }
+    .render()
+    .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tolerance() -> Tolerance {
+        Tolerance { throughput: 5., response_time: 10., error: 1., resource: 10. }
+    }
+
+    fn summary(transactions: &[(&str, Metric, f64)], hosts: &[(&str, Metric, f64)]) -> RunSummary {
+        let mut summary = RunSummary::default();
+        for (group, values) in [(&mut summary.transactions, transactions), (&mut summary.hosts, hosts)] {
+            for (name, metric, value) in values.iter() {
+                group.entry(name.to_string()).or_default().insert(*metric, *value);
+            }
+        }
+        summary
+    }
+
+    fn row<'a>(rows: &'a [DiffRow], name: &str, metric: Metric) -> &'a DiffRow {
+        rows.iter().find(|r| r.name == name && r.metric == metric).unwrap()
+    }
+
+    #[test]
+    fn worse_beyond_tolerance_is_regression() {
+        let baseline = summary(&[("Total", Metric::Throughput, 100.), ("Total", Metric::P95, 200.), ("Total", Metric::Error, 0.5)], &[("app01", Metric::CpuMean, 40.)]);
+        let candidate = summary(&[("Total", Metric::Throughput, 94.), ("Total", Metric::P95, 221.), ("Total", Metric::Error, 1.6)], &[("app01", Metric::CpuMean, 50.5)]);
+        let rows = compare(&baseline, &candidate, &tolerance());
+        assert_eq!(rows.len(), 4);
+        assert!(rows.iter().all(|r| r.regression));
+        assert_eq!(row(&rows, "Total", Metric::Throughput).change_text(), "-6.00%");
+        assert_eq!(row(&rows, "app01", Metric::CpuMean).change_text(), "+10.50pp");
+    }
+
+    #[test]
+    fn worse_within_tolerance_is_not_regression() {
+        let baseline = summary(&[("Total", Metric::Throughput, 100.), ("Total", Metric::P95, 200.), ("Total", Metric::Error, 0.5)], &[("app01", Metric::CpuMean, 40.)]);
+        let candidate = summary(&[("Total", Metric::Throughput, 96.), ("Total", Metric::P95, 219.), ("Total", Metric::Error, 1.4)], &[("app01", Metric::CpuMean, 49.5)]);
+        let rows = compare(&baseline, &candidate, &tolerance());
+        assert_eq!(rows.len(), 4);
+        assert!(rows.iter().all(|r| !r.regression && r.change.is_some()));
+        assert!(rows.iter().all(|r| r.result_text() == "OK"));
+    }
+
+    #[test]
+    fn missing_from_baseline_is_not_regression() {
+        let baseline = summary(&[("Total", Metric::P95, 200.)], &[]);
+        let candidate = summary(&[("Total", Metric::P95, 200.), ("Total", Metric::Error, 50.), ("login", Metric::P95, 9999.)], &[("db01", Metric::CpuMax, 100.)]);
+        let rows = compare(&baseline, &candidate, &tolerance());
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[0].name, "Total");
+        for (name, metric) in [("Total", Metric::Error), ("login", Metric::P95), ("db01", Metric::CpuMax)] {
+            let row = row(&rows, name, metric);
+            assert_eq!((row.baseline, row.change, row.regression), (None, None, false));
+            assert_eq!(row.result_text(), "-");
+        }
+    }
+
+    #[test]
+    fn zero_baseline_has_no_relative_change() {
+        let baseline = summary(&[("Total", Metric::Mean, 0.)], &[]);
+        let candidate = summary(&[("Total", Metric::Mean, 10.)], &[]);
+        let rows = compare(&baseline, &candidate, &tolerance());
+        assert_eq!((rows[0].change, rows[0].regression), (None, false));
+    }
+}
@@ -0,0 +1,139 @@
+use globset::Glob;
+use globset::GlobSet;
+use globset::GlobSetBuilder;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::Measurement;
+
+/// 自定义图表的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartKind {
+    /// 各列随时间变化的折线图
+    Line,
+    /// 各列随时间变化的堆叠面积图
+    Area,
+    /// 各列每个时间点之和随时间变化的折线图
+    Sum,
+    /// 各列的Avg.、WAvg.、Max.、Min.柱状图
+    Awmn,
+}
+
+impl Display for ChartKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            ChartKind::Line => "line",
+            ChartKind::Area => "area",
+            ChartKind::Sum => "sum",
+            ChartKind::Awmn => "awmn",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl FromStr for ChartKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "line" => Ok(ChartKind::Line),
+            "area" => Ok(ChartKind::Area),
+            "sum" => Ok(ChartKind::Sum),
+            "awmn" => Ok(ChartKind::Awmn),
+            _ => Err(format!("图表类型'{}'不正确，只支持line、area、sum、awmn", s)),
+        }
+    }
+}
+
+/// 自定义图表，格式为: SECTION:COLUMN[,COLUMN...][:KIND]，COLUMN可以是glob，KIND默认为line，比如:
+/// MEM:memfree,cached,buffers:line
+/// DISKBUSY:*:awmn
+/// DISKREAD:sd*:area
+#[derive(Debug, Clone)]
+pub struct ChartSpec {
+    raw: String,
+    section: String,
+    columns: GlobSet,
+    kind: ChartKind,
+    // 报告定义文件里指定的图表标题和单位
+    title: Option<String>,
+    unit: Option<String>,
+}
+
+impl FromStr for ChartSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let usage = || format!("图表'{}'格式不正确，应为: SECTION:COLUMN[,COLUMN...][:KIND]，比如: \"MEM:memfree,cached,buffers:line\"", s);
+        let vec: Vec<&str> = s.trim().splitn(3, ':').collect();
+        let (section, columns, kind) = match vec[..] {
+            [section, columns] => (section, columns, ChartKind::Line),
+            [section, columns, kind] => (section, columns, ChartKind::from_str(kind)?),
+            _ => return Err(usage()),
+        };
+        if section.is_empty() || columns.is_empty() {
+            return Err(usage());
+        }
+        let columns: Vec<&str> = columns.split(',').filter(|c| !c.is_empty()).collect();
+        Self::new(section, &columns, kind)
+    }
+}
+
+/// 图表名称，也用于图表元素的id
+impl Display for ChartSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.raw)
+    }
+}
+
+impl ChartSpec {
+    /// columns: 列名或glob，比如: sd*
+    pub fn new(section: &str, columns: &[&str], kind: ChartKind) -> Result<Self, String> {
+        let raw = format!("{}:{}:{}", section, columns.join(","), kind);
+        let mut builder = GlobSetBuilder::new();
+        for column in columns.iter() {
+            builder.add(Glob::new(column).map_err(|e| format!("图表'{}'的列'{}'不正确: {}", raw, column, e))?);
+        }
+        Ok(Self {
+            section: section.to_string(),
+            columns: builder.build().map_err(|e| format!("图表'{}'的列不正确: {}", raw, e))?,
+            kind,
+            title: None,
+            unit: None,
+            raw,
+        })
+    }
+
+    /// 设置图表的标题和Y轴的单位
+    pub fn with_label(mut self, title: Option<String>, unit: Option<String>) -> Self {
+        self.title = title;
+        self.unit = unit;
+        self
+    }
+
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    pub fn unit(&self) -> Option<&str> {
+        self.unit.as_deref()
+    }
+
+    pub fn section(&self) -> &str {
+        &self.section
+    }
+
+    pub fn kind(&self) -> ChartKind {
+        self.kind
+    }
+
+    /// 指标里匹配的列，按nmon文件里列的顺序
+    pub fn columns<'a>(&self, measurement: &'a Measurement) -> Vec<&'a str> {
+        measurement
+            .header()
+            .iter()
+            .map(|h| h.as_str())
+            .filter(|h| self.columns.is_match(h))
+            .collect()
+    }
+}
@@ -0,0 +1,298 @@
+//! rnmon命令行程序: 命令行参数及各子命令，src/main.rs只调用这里的main
+
+use clap::{ArgAction, Parser, Subcommand};
+use chrono::{offset::Local};
+use log::info;
+use log::error;
+use std::time::SystemTime;
+use anyhow::Result;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::NmonArgs;
+use crate::TimeBound;
+use crate::baseline;
+use crate::baseline::RunSummary;
+use crate::baseline::Tolerance;
+use crate::nmon;
+use crate::output;
+use crate::rule;
+use crate::export::ExportFormat;
+use crate::serve::ReportServer;
+use crate::watch::Watcher;
+
+/// rnmon命令行程序的入口: 解析命令行参数、设置日志，执行子命令或分析nmon文件
+pub fn main() -> Result<()>{
+
+    // 处理传入的程序的参数
+    let cli_args = Args::parse();
+
+    // log处理
+    let mut log = fern::Dispatch::new()
+        .format(|out, message, record| {
+            out.finish(format_args!(
+                "[{} {} {}] {}",
+                chrono::DateTime::<Local>::from(SystemTime::now()).format("%Y-%m-%d %H:%M:%S%.3f"),
+                record.level(),
+                record.target(),
+                message
+            ))
+        })
+        .chain(std::io::stdout());
+
+    // log = if let Some(ref logfile) = cli_args.logfile {
+    //     log.chain(fern::log_file(logfile)?)
+    // } else {
+    //     log
+    // };
+
+    log = match cli_args.debug {
+        0 => log.level(log::LevelFilter::Info),
+        1 => log.level(log::LevelFilter::Debug),
+        _ => log.level(log::LevelFilter::Trace),
+    };
+
+    info!("cli_args: {:?}", &cli_args);
+    log.apply()?;
+
+
+    match cli_args.command {
+        Some(Commands::Export(export_args)) => return export(export_args),
+        Some(Commands::Watch(watch_args)) => return watch(watch_args),
+        Some(Commands::Serve(serve_args)) => return serve(serve_args),
+        Some(Commands::Compare(compare_args)) => return compare(compare_args),
+        None => {}
+    }
+
+    let nmon_args = cli_args.nmon_args;
+    if cli_args.list_sections {
+        return list_sections(&nmon_args);
+    }
+
+    let rules = nmon_args.rules().map_err(|e| anyhow::anyhow!(e))?;
+    let mut cell = nmon_args.metrics_name();
+    // 规则里的指标也需要解析
+    cell.extend(rules.iter().map(|rule| rule.section().to_string()));
+    let (nmon_datas, skipped) = nmon_args.nmon_dates(&cell)?;
+    let path = nmon_args.html_output_folder();
+    // nmon::nmon(nmon_datas, &cell, path, nmon_args)?;
+    nmon(&nmon_datas,  path.clone(), &nmon_args)?;
+
+    // --lenient时，最后打印各nmon文件跳过的数据
+    if nmon_args.run_nmon_args().lenient {
+        output::console_print_diagnostics(&nmon_datas, &skipped);
+    }
+
+    // 判断阈值规则，有不通过的规则时以非0退出
+    if !rules.is_empty() {
+        let results = rule::evaluate(&rules, &nmon_datas);
+        let res = output::console_print_rules(&results);
+        output::save(path.join("res.rules.txt"), res);
+        let failed = results.iter().filter(|r| !r.passed).count();
+        if failed > 0 {
+            error!("{}条阈值规则不通过，退出码: {}", failed, rule::RULE_FAILED_EXIT_CODE);
+            std::process::exit(rule::RULE_FAILED_EXIT_CODE);
+        }
+    }
+
+    Ok(())
+}
+
+/// 列出nmon文件里的所有指标
+fn list_sections(nmon_args: &NmonArgs) -> Result<()> {
+    let (files, skipped) = nmon_args.list_sections()?;
+    output::console_print_sections(&files);
+    for err in skipped.iter() {
+        error!("跳过: {}", err);
+    }
+    Ok(())
+}
+
+/// 导出解析后的nmon指标数据
+fn export(export_args: ExportArgs) -> Result<()> {
+    let nmon_args = export_args.nmon_args;
+    let cell = if export_args.section.is_empty() {
+        nmon_args.metrics_name()
+    } else {
+        export_args.section.iter().cloned().collect()
+    };
+    let (nmon_datas, skipped) = nmon_args.nmon_dates(&cell)?;
+    let files = crate::export::export(&nmon_datas, &export_args.output, export_args.format)?;
+    info!("共导出{}个文件到: {}", files.len(), export_args.output.display());
+
+    if nmon_args.run_nmon_args().lenient {
+        output::console_print_diagnostics(&nmon_datas, &skipped);
+    }
+    Ok(())
+}
+
+/// 跟踪正在写入的nmon文件，定时重新生成HTML报告，直到Ctrl+C
+fn watch(watch_args: WatchArgs) -> Result<()> {
+    let mut nmon_args = watch_args.nmon_args;
+    nmon_args.set_live(watch_args.interval);
+    let cell = nmon_args.metrics_name();
+    let path = nmon_args.html_output_folder();
+    let mut watcher = Watcher::new(nmon_args.clone(), cell)?;
+    info!("每{}秒检查nmon文件，打开{}查看图表，Ctrl+C退出", watch_args.interval, path.join("index_nmons.html").display());
+    loop {
+        if watcher.poll()? && !watcher.datas().is_empty() {
+            nmon(watcher.datas(), path.clone(), &nmon_args)?;
+            info!("已按{}个nmon文件的最新数据更新图表", watcher.datas().len());
+        }
+        std::thread::sleep(Duration::from_secs(watch_args.interval));
+    }
+}
+
+
+/// 在本地端口上提供测试结果目录里的报告
+fn serve(serve_args: ServeArgs) -> Result<()> {
+    let server = ReportServer::new(serve_args.root)?;
+    server.run(&format!("{}:{}", serve_args.bind, serve_args.port))
+}
+
+/// 对比两次测试的JMeter统计值和各服务器的资源使用，有回归时以非0退出
+fn compare(compare_args: CompareArgs) -> Result<()> {
+    let (from, to, lenient) = (compare_args.from, compare_args.to, compare_args.lenient);
+    let baseline = RunSummary::read(&compare_args.baseline, from.clone(), to.clone(), lenient)?;
+    let candidate = RunSummary::read(&compare_args.candidate, from, to, lenient)?;
+    let tolerance = Tolerance {
+        throughput: compare_args.throughput_tolerance,
+        response_time: compare_args.rt_tolerance,
+        error: compare_args.error_tolerance,
+        resource: compare_args.resource_tolerance,
+    };
+    let rows = baseline::compare(&baseline, &candidate, &tolerance);
+
+    let path = compare_args.output;
+    std::fs::create_dir_all(&path)?;
+    let res = output::console_print_compare(&rows);
+    output::save(path.join("res.compare.txt"), res);
+    let (a, b) = (baseline.path().display().to_string(), candidate.path().display().to_string());
+    output::save(path.join("res.compare.md"), output::markdown_compare(&a, &b, &rows));
+    output::save(path.join("res.compare.html"), baseline::html(&baseline, &candidate, &tolerance, &rows));
+    info!("对比报告: {}", path.join("res.compare.html").display());
+
+    let regressions = rows.iter().filter(|r| r.regression).count();
+    if regressions > 0 {
+        error!("{}项指标超过容差，退出码: {}", regressions, baseline::REGRESSION_EXIT_CODE);
+        std::process::exit(baseline::REGRESSION_EXIT_CODE);
+    }
+    Ok(())
+}
+
+/// /// 分析nmon文件，打印各nmon文件的cpu使用，可生成HTML图表（echars.js和html文件）
+#[derive(Parser, Debug)]
+#[command(author = "liuqxx", version = "0.1.0")]
+pub(crate) struct Args {
+    /// 一个开启DEBUG日志，两个及以上开启trace日志
+    #[arg(short, long, action = ArgAction::Count)]
+    pub debug: u8,
+
+    /// 列出nmon文件里的所有指标、列名及行数，不生成报告。--section、--report的sections可以用其中的指标名称或glob
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub list_sections: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+
+    #[command(flatten)]
+    pub nmon_args: NmonArgs,
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum Commands {
+    /// 把解析后的nmon指标数据(列名、ZZZZ时间序列、数据矩阵)导出为CSV或JSON文件，每个nmon文件的每个指标一个文件
+    Export(ExportArgs),
+    /// 跟踪正在写入的nmon文件(测试运行中)，定时增量读取新的快照并更新HTML报告，报告页面自动刷新图表
+    Watch(WatchArgs),
+    /// 在本地端口上提供测试结果目录里的nmon图表、JMeter报告，首页列出各目录下rmeter生成的测试结果目录
+    Serve(ServeArgs),
+    /// 对比两次测试的结果目录(基线与候选)，包括JMeter各事务的res/statistics.json和各服务器nmon的CPU、内存、DISKBUSY，
+    /// 生成res.compare.txt、res.compare.md、res.compare.html，有指标超过容差时退出码为4
+    Compare(CompareArgs),
+}
+
+#[derive(Parser, Debug, Clone)]
+pub(crate) struct CompareArgs {
+    /// 基线的测试结果目录
+    pub baseline: PathBuf,
+
+    /// 候选(调优后)的测试结果目录
+    pub candidate: PathBuf,
+
+    /// 对比报告的输出目录，不存在时自动创建
+    #[arg(long, value_name = "DIR", default_value = "./")]
+    pub output: PathBuf,
+
+    /// 吞吐量下降超过该百分比时为回归
+    #[arg(long, value_name = "PCT", default_value_t = 5.0)]
+    pub throughput_tolerance: f64,
+
+    /// 平均、p90、p95、p99响应时间上升超过该百分比时为回归
+    #[arg(long, value_name = "PCT", default_value_t = 10.0)]
+    pub rt_tolerance: f64,
+
+    /// 错误率上升超过该百分点时为回归
+    #[arg(long, value_name = "PP", default_value_t = 0.5)]
+    pub error_tolerance: f64,
+
+    /// CPU、内存、DISKBUSY使用率的均值或最大值上升超过该百分点时为回归
+    #[arg(long, value_name = "PP", default_value_t = 10.0)]
+    pub resource_tolerance: f64,
+
+    /// 只统计该时间段内的资源使用，格式同rnmon的--from
+    #[arg(long, value_name = "TIME", allow_hyphen_values = true)]
+    pub from: Option<TimeBound>,
+
+    /// 只统计该时间段内的资源使用，格式同rnmon的--to
+    #[arg(long, value_name = "TIME", allow_hyphen_values = true)]
+    pub to: Option<TimeBound>,
+
+    /// 宽松模式，跳过无法解析的nmon数据或文件
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub lenient: bool,
+}
+
+#[derive(Parser, Debug, Clone)]
+pub(crate) struct ServeArgs {
+    /// 监听的地址，团队共享时可以指定为0.0.0.0
+    #[arg(long, default_value = "127.0.0.1")]
+    pub bind: String,
+
+    /// 监听的端口
+    #[arg(long, default_value_t = 8080)]
+    pub port: u16,
+
+    /// 测试结果目录或其上级目录，可指定多个
+    #[arg(default_value = ".")]
+    pub root: Vec<PathBuf>,
+}
+
+#[derive(Parser, Debug, Clone)]
+pub(crate) struct WatchArgs {
+    /// 检查nmon文件和报告页面刷新的间隔(秒)
+    #[arg(long, value_name = "SECONDS", default_value_t = 10, value_parser = clap::value_parser!(u64).range(1..))]
+    pub interval: u64,
+
+    #[command(flatten)]
+    pub nmon_args: NmonArgs,
+}
+
+#[derive(Parser, Debug, Clone)]
+pub(crate) struct ExportArgs {
+    /// 导出文件的格式
+    #[arg(long, value_enum, default_value_t = ExportFormat::Csv)]
+    pub format: ExportFormat,
+
+    /// 导出文件的输出目录，不存在时自动创建
+    #[arg(long, value_name = "DIR", default_value = "./export")]
+    pub output: PathBuf,
+
+    /// 要导出的指标名称或glob，可指定多次，比如: --section CPU_ALL --section MEM --section "DISK*"，--section "*"导出所有指标。
+    /// 不指定时导出按--cpu、--disk-busy、--net等参数解析的指标
+    #[arg(long, value_name = "NAME", action = ArgAction::Append)]
+    pub section: Vec<String>,
+
+    #[command(flatten)]
+    pub nmon_args: NmonArgs,
+}
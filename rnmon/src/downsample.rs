@@ -0,0 +1,128 @@
+/// Largest-Triangle-Three-Buckets降采样，返回保留的点的下标，保留第一个和最后一个点。
+/// 除首尾外按threshold - 2个桶分组，每个桶保留与前一个保留点、下一个桶平均点组成的三角形面积最大的点，
+/// 峰值和谷值通常面积最大，因此降采样后仍可见。nmon快照的间隔固定，x取下标。
+/// 同一图表里多条曲线共用降采样下标，各曲线保留相同的时间点，堆叠图和成对的曲线才能对齐：
+/// 沿共同的x轴只分一次桶，每个桶保留各曲线三角形面积的最大值最大的点，共threshold个点。
+/// 各曲线的面积按其取值范围归一化，单位不同(比如CPU%与IO/s)的曲线的峰值都能保留
+pub fn lttb_shared(series: &[Vec<f32>], threshold: usize) -> Vec<usize> {
+    let len = series.iter().map(|ys| ys.len()).min().unwrap_or_default();
+    if threshold >= len || threshold < 3 {
+        return (0..len).collect();
+    }
+    let scales: Vec<f64> = series
+        .iter()
+        .map(|ys| {
+            let (min, max) = ys[..len].iter().filter(|y| !y.is_nan())
+                .fold((f32::MAX, f32::MIN), |(min, max), y| (min.min(*y), max.max(*y)));
+            if max > min { (max - min) as f64 } else { 1. }
+        })
+        .collect();
+    let every = (len - 2) as f64 / (threshold - 2) as f64;
+    let mut sampled = Vec::with_capacity(threshold);
+    let mut a = 0;
+    sampled.push(a);
+    for i in 0..threshold - 2 {
+        // 下一个桶的平均点，各曲线的x相同
+        let avg_start = ((i + 1) as f64 * every) as usize + 1;
+        let avg_end = (((i + 2) as f64 * every) as usize + 1).min(len);
+        let avg_x = if avg_start < avg_end { (avg_start + avg_end - 1) as f64 / 2. } else { (len - 1) as f64 };
+        let avg_ys: Vec<f64> = series
+            .iter()
+            .map(|ys| {
+                if avg_start < avg_end {
+                    let n = (avg_end - avg_start) as f64;
+                    ys[avg_start..avg_end].iter().map(|y| *y as f64).sum::<f64>() / n
+                } else {
+                    ys[len - 1] as f64
+                }
+            })
+            .collect();
+
+        // 当前桶里各曲线面积最大值最大的点
+        let start = (i as f64 * every) as usize + 1;
+        let end = (((i + 1) as f64 * every) as usize + 1).min(len - 1);
+        let ax = a as f64;
+        let mut max_area = -1.;
+        let mut next = start;
+        for j in start..end {
+            let area = series
+                .iter()
+                .zip(avg_ys.iter())
+                .zip(scales.iter())
+                .map(|((ys, avg_y), scale)| {
+                    let ay = ys[a] as f64;
+                    ((ax - avg_x) * (ys[j] as f64 - ay) - (ax - j as f64) * (avg_y - ay)).abs() / scale
+                })
+                .filter(|area| !area.is_nan())
+                .fold(0., f64::max);
+            if area > max_area {
+                max_area = area;
+                next = j;
+            }
+        }
+        sampled.push(next);
+        a = next;
+    }
+    sampled.push(len - 1);
+    sampled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wave(len: usize) -> Vec<f32> {
+        (0..len).map(|i| (i as f32 / 5.).sin()).collect()
+    }
+
+    #[test]
+    fn lttb_keeps_threshold_points() {
+        let idxs = lttb_shared(&[wave(1000)], 100);
+        assert_eq!(idxs.len(), 100);
+        assert_eq!(idxs[0], 0);
+        assert_eq!(idxs[99], 999);
+        assert!(idxs.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn lttb_keeps_peak() {
+        let mut ys = vec![1.; 1000];
+        ys[437] = 100.;
+        ys[712] = -50.;
+        let idxs = lttb_shared(&[ys], 20);
+        assert!(idxs.contains(&437));
+        assert!(idxs.contains(&712));
+    }
+
+    #[test]
+    fn lttb_short_series_unchanged() {
+        assert_eq!(lttb_shared(&[wave(10)], 20), (0..10).collect::<Vec<_>>());
+        assert_eq!(lttb_shared(&[wave(10)], 2), (0..10).collect::<Vec<_>>());
+        assert!(lttb_shared(&[], 20).is_empty());
+    }
+
+    #[test]
+    fn lttb_shared_keeps_peaks_of_every_series() {
+        let mut a = vec![1.; 1000];
+        let mut b = vec![1.; 1000];
+        a[100] = 100.;
+        // 单位不同的曲线，峰值也要保留
+        b[900] = 0.5;
+        let idxs = lttb_shared(&[a, b], 40);
+        assert!(idxs.contains(&0) && idxs.contains(&999));
+        assert!(idxs.contains(&100) && idxs.contains(&900));
+        assert!(idxs.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn lttb_shared_keeps_threshold_points() {
+        let series: Vec<Vec<f32>> = (0..2).map(|_| wave(1000)).collect();
+        assert_eq!(lttb_shared(&series, 10).len(), 10);
+        // 曲线很多时(比如100个CPU)，点数仍为threshold，而不是按曲线数平分
+        let series: Vec<Vec<f32>> = (0..100).map(|i| wave(5000 + i)).collect();
+        let idxs = lttb_shared(&series, 2000);
+        assert_eq!(idxs.len(), 2000);
+        assert!(idxs.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(*idxs.last().unwrap(), 4999);
+    }
+}
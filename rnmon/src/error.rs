@@ -0,0 +1,124 @@
+use std::fmt::Display;
+use std::path::PathBuf;
+
+/// 解析nmon文件时的错误，带文件名、行号、指标名称等上下文
+#[derive(Debug)]
+pub enum NmonError {
+    /// 文件或目录读取失败
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// zip压缩包读取失败
+    Archive {
+        path: PathBuf,
+        message: String,
+    },
+    /// --include/--exclude的glob不正确
+    Glob {
+        message: String,
+    },
+    /// JMeter结果文件(res.jtl)解析失败
+    Jtl {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+    /// 输入参数即不是目录也不是文件
+    InvalidPath {
+        path: PathBuf,
+    },
+    /// 某行数据读取失败，比如非UTF-8编码
+    Line {
+        file: String,
+        line: usize,
+        source: std::io::Error,
+    },
+    /// ZZZZ时间序列解析失败
+    Timestamp {
+        file: String,
+        line: usize,
+        value: String,
+        source: chrono::ParseError,
+    },
+    /// 指标数据无法转换为数字
+    Value {
+        file: String,
+        line: usize,
+        section: String,
+        column: String,
+        value: String,
+    },
+    /// 指标数据长度小于列名长度，数据不完整
+    Incomplete {
+        file: String,
+        line: usize,
+        section: String,
+        expected: usize,
+        actual: usize,
+    },
+    /// --from/--to指定的时间段内没有数据
+    EmptyWindow {
+        file: String,
+        first: String,
+        last: String,
+    },
+    /// 指标数据从vec转化为Array2失败
+    Shape {
+        file: String,
+        section: String,
+        rows: usize,
+        cols: usize,
+    },
+}
+
+impl Display for NmonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NmonError::Io { path, source } => {
+                write!(f, "{}读取失败: {}", path.display(), source)
+            }
+            NmonError::Archive { path, message } => {
+                write!(f, "{}压缩包读取失败: {}", path.display(), message)
+            }
+            NmonError::Glob { message } => {
+                write!(f, "--include/--exclude的glob不正确: {}", message)
+            }
+            NmonError::Jtl { path, line, message } => {
+                write!(f, "{}文件第{}行解析失败: {}", path.display(), line, message)
+            }
+            NmonError::InvalidPath { path } => {
+                write!(f, "{}即不是目录也不是文件", path.display())
+            }
+            NmonError::Line { file, line, source } => {
+                write!(f, "{}文件第{}行读取失败: {}", file, line, source)
+            }
+            NmonError::Timestamp { file, line, value, source } => {
+                write!(f, "{}文件第{}行, [ZZZZ]时间序列解析出错: {}, 原始数据: '{}'", file, line, source, value)
+            }
+            NmonError::Value { file, line, section, column, value } => {
+                write!(f, "{}文件第{}行, [{}]指标[{}]列的数据'{}'无法转换为数字", file, line, section, column, value)
+            }
+            NmonError::Incomplete { file, line, section, expected, actual } => {
+                write!(f, "{}文件第{}行, [{}]指标数据长度[{}]小于列名长度[{}]，数据不完整", file, line, section, actual, expected)
+            }
+            NmonError::EmptyWindow { file, first, last } => {
+                write!(f, "{}文件，--from/--to指定的时间段内没有数据，该文件的时间范围为: {} ~ {}", file, first, last)
+            }
+            NmonError::Shape { file, section, rows, cols } => {
+                write!(f, "{}文件，{}指标从vec转化为Array2({}, {})失败", file, section, rows, cols)
+            }
+        }
+    }
+}
+
+impl std::error::Error for NmonError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            NmonError::Io { source, .. } => Some(source),
+            NmonError::Line { source, .. } => Some(source),
+            NmonError::Timestamp { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use clap::ValueEnum;
+use log::info;
+use serde_json::json;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::Measurement;
+use crate::NmonData;
+use crate::askama::POINT_X_FORMAT;
+use crate::error::NmonError;
+
+/// 导出文件的格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// 第一列为ZZZZ时间序列，其余列为指标的各列
+    Csv,
+    /// 包含文件名、服务器、指标名称、列名、ZZZZ时间序列和数据矩阵(按行)
+    Json,
+}
+
+impl ExportFormat {
+    fn extension(&self) -> &str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+/// 把各nmon文件的每个指标导出为一个文件，文件名为: nmon文件名(不含.nmon)_指标名称.csv，返回导出的文件
+pub fn export(nmon_datas: &[NmonData], dir: &Path, format: ExportFormat) -> Result<Vec<PathBuf>, NmonError> {
+    std::fs::create_dir_all(dir).map_err(|e| NmonError::Io { path: dir.to_path_buf(), source: e })?;
+    let mut files = Vec::new();
+    for ndata in nmon_datas.iter() {
+        let stem = ndata.filename().trim_end_matches(".nmon");
+        for (name, measurement) in ndata.measurements().iter() {
+            let path = dir.join(format!("{}_{}.{}", file_safe(stem), file_safe(name), format.extension()));
+            let content = match format {
+                ExportFormat::Csv => to_csv(measurement).map_err(|e| NmonError::Io { path: path.clone(), source: e.into() })?,
+                ExportFormat::Json => to_json(ndata, measurement).into_bytes(),
+            };
+            std::fs::write(&path, content).map_err(|e| NmonError::Io { path: path.clone(), source: e })?;
+            info!("[{}]文件, [{}]指标导出到: {}", ndata.filename(), name, path.display());
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+/// 指标名称里可能有文件名不支持的字符，比如: /
+fn file_safe(s: &str) -> String {
+    s.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' { c } else { '_' })
+        .collect()
+}
+
+/// 列名里的逗号、双引号由csv::Writer负责转义，NaN写为NaN
+fn to_csv(measurement: &Measurement) -> Result<Vec<u8>, csv::Error> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(std::iter::once("ZZZZ").chain(measurement.header().iter().map(|h| h.as_str())))?;
+    for (x, row) in measurement.zzzz().iter().zip(measurement.data().rows()) {
+        let mut record = vec![format!("{}", x.format(POINT_X_FORMAT))];
+        record.extend(row.iter().map(|v| v.to_string()));
+        writer.write_record(&record)?;
+    }
+    writer.into_inner().map_err(|e| e.into_error().into())
+}
+
+/// f32转f64时保留f32的最短十进制表示，避免12.4变成12.399999618530273
+fn json_number(v: f32) -> serde_json::Value {
+    v.to_string().parse::<f64>().map(|v| json!(v)).unwrap_or(serde_json::Value::Null)
+}
+
+fn to_json(ndata: &NmonData, measurement: &Measurement) -> String {
+    let zzzz: Vec<String> = measurement.zzzz().iter().map(|x| format!("{}", x.format(POINT_X_FORMAT))).collect();
+    let data: Vec<Vec<serde_json::Value>> = measurement
+        .data()
+        .rows()
+        .into_iter()
+        .map(|row| row.iter().map(|v| json_number(*v)).collect())
+        .collect();
+    let value = json!({
+        "file": ndata.filename(),
+        "host": ndata.host().host(),
+        "section": measurement.name(),
+        "note": measurement.note(),
+        "header": measurement.header(),
+        "zzzz": zzzz,
+        "data": data,
+    });
+    serde_json::to_string_pretty(&value).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ParseOptions;
+    use chrono::FixedOffset;
+    use chrono::TimeZone;
+    use std::collections::HashSet;
+    use std::io::Cursor;
+
+    #[test]
+    fn csv_round_trip_quotes_header_and_keeps_nan() {
+        let tz = FixedOffset::east_opt(8 * 3600).unwrap();
+        let zzzz = vec![
+            tz.with_ymd_and_hms(2020, 6, 16, 17, 40, 1).unwrap(),
+            tz.with_ymd_and_hms(2020, 6, 16, 17, 40, 2).unwrap(),
+        ];
+        let header = vec!["User,%".to_string(), "Idle \"x\"".to_string()];
+        let measurement =
+            Measurement::new("app01.nmon", "CPU_ALL", "CPU Total", header, &zzzz, vec![10., 90., f32::NAN, 80.], (2, 2)).unwrap();
+        let content = to_csv(&measurement).unwrap();
+
+        let mut reader = csv::Reader::from_reader(content.as_slice());
+        let headers: Vec<String> = reader.headers().unwrap().iter().map(|h| h.to_string()).collect();
+        assert_eq!(headers, vec!["ZZZZ", "User,%", "Idle \"x\""]);
+        let records: Vec<csv::StringRecord> = reader.records().map(|r| r.unwrap()).collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(&records[0][1], "10");
+        assert!(records[1][1].parse::<f32>().unwrap().is_nan());
+        assert_eq!(records[1][2].parse::<f32>().unwrap(), 80.);
+    }
+
+    #[test]
+    fn json_writes_nan_as_null() {
+        let nmon = "CPU_ALL,CPU Total app01,User%,Idle%\n\
+                    ZZZZ,T0001,17:40:01,16-JUN-2020\n\
+                    CPU_ALL,T0001,12.4,87.6\n\
+                    ZZZZ,T0002,17:40:02,16-JUN-2020\n\
+                    CPU_ALL,T0002,abc,80.0\n";
+        let cell: HashSet<String> = ["CPU_ALL".to_string()].into_iter().collect();
+        let options = ParseOptions { lenient: true, ..Default::default() };
+        let ndata = NmonData::from_reader(Cursor::new(nmon.to_string()), "app01.nmon", &cell, &options).unwrap();
+        let measurement = ndata.measurement("CPU_ALL").unwrap();
+
+        let value: serde_json::Value = serde_json::from_str(&to_json(&ndata, measurement)).unwrap();
+        assert_eq!(value["section"], "CPU_ALL");
+        assert_eq!(value["header"], json!(["User%", "Idle%"]));
+        assert_eq!(value["data"], json!([[12.4, 87.6], [null, 80.0]]));
+        assert_eq!(value["zzzz"].as_array().unwrap().len(), 2);
+    }
+}
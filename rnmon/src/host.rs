@@ -0,0 +1,159 @@
+use std::collections::BTreeMap;
+
+// AAA,progname,nmon
+// AAA,command,nmon -F /perf/res.nmon -t -s 2 -c 30
+// AAA,version,16g
+// AAA,host,app01
+// AAA,OS,Linux,3.10.0-1160.el7.x86_64,#1 SMP Mon Oct 19 16:18:59 UTC 2020,x86_64
+// AAA,time,17:40:19
+// AAA,date,16-JUN-2020
+// AAA,interval,2
+// AAA,snapshots,30
+// AAA,cpus,4,4
+// AAA,x86,ModelName,Intel(R) Xeon(R) CPU E5-2680 v4 @ 2.40GHz
+// ...
+// BBBP,005,/proc/meminfo
+// BBBP,006,/proc/meminfo,"MemTotal:       16266704 kB"
+//
+// AIX的nmon文件头没有OS，而是:
+// AAA,AIX,7.2.4.2
+// AAA,hardware,Architecture PowerPC Implementation POWER9 64 bit
+//
+/// nmon文件的平台，不同平台的指标和列不同
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    Linux,
+    Aix,
+}
+
+impl std::fmt::Display for Platform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Platform::Linux => write!(f, "Linux"),
+            Platform::Aix => write!(f, "AIX"),
+        }
+    }
+}
+
+/// nmon文件头AAA、BBBP里的服务器信息
+#[derive(Debug, Default, Clone)]
+pub struct HostInfo {
+    host: Option<String>,
+    os: Option<String>,
+    version: Option<String>,
+    cpus: Option<String>,
+    cpu_model: Option<String>,
+    interval: Option<String>,
+    snapshots: Option<String>,
+    date: Option<String>,
+    time: Option<String>,
+    command: Option<String>,
+    // AAA里所有的key和value，包括上面没有单独列出的
+    aaa: BTreeMap<String, String>,
+    // BBBP按命令或文件名(比如/proc/cpuinfo、lsblk)分组的输出内容
+    bbbp: BTreeMap<String, Vec<String>>,
+}
+
+impl HostInfo {
+    pub fn push_aaa(&mut self, vec: &[&str]) {
+        if vec.len() < 3 {
+            return;
+        }
+        let key = vec[1];
+        let value = vec[2..].join(",");
+        match key {
+            "host" => self.host = Some(value.clone()),
+            "OS" => self.os = Some(vec[2..].join(" ")),
+            "AIX" => self.os = Some(format!("AIX {}", value)),
+            "version" => self.version = Some(value.clone()),
+            "cpus" => self.cpus = Some(vec[2].to_string()),
+            "interval" => self.interval = Some(value.clone()),
+            "snapshots" => self.snapshots = Some(value.clone()),
+            "date" => self.date = Some(value.clone()),
+            "time" => self.time = Some(value.clone()),
+            "command" => self.command = Some(value.clone()),
+            "x86" if vec.len() > 3 && vec[2] == "ModelName" => self.cpu_model = Some(vec[3..].join(",")),
+            _ => {}
+        }
+        self.aaa.insert(key.to_string(), value);
+    }
+
+    pub fn push_bbbp(&mut self, vec: &[&str]) {
+        if vec.len() < 3 {
+            return;
+        }
+        let lines = self.bbbp.entry(vec[2].to_string()).or_default();
+        if vec.len() > 3 {
+            let line = vec[3..].join(",");
+            lines.push(line.trim_matches('"').to_string());
+        }
+    }
+
+    pub fn host(&self) -> Option<&str> {
+        self.host.as_deref()
+    }
+    pub fn os(&self) -> Option<&str> {
+        self.os.as_deref()
+    }
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+    pub fn cpus(&self) -> Option<&str> {
+        self.cpus.as_deref()
+    }
+    pub fn interval(&self) -> Option<&str> {
+        self.interval.as_deref()
+    }
+    pub fn snapshots(&self) -> Option<&str> {
+        self.snapshots.as_deref()
+    }
+    /// AAA里有AIX版本时为AIX，否则按Linux处理
+    pub fn platform(&self) -> Platform {
+        if self.aaa.contains_key("AIX") || self.os.as_deref().is_some_and(|os| os.starts_with("AIX")) {
+            Platform::Aix
+        } else {
+            Platform::Linux
+        }
+    }
+    pub fn aaa(&self) -> &BTreeMap<String, String> {
+        &self.aaa
+    }
+    pub fn bbbp(&self) -> &BTreeMap<String, Vec<String>> {
+        &self.bbbp
+    }
+
+    /// /proc/meminfo里的MemTotal，单位MB
+    pub fn mem_total_mb(&self) -> Option<f32> {
+        self.bbbp
+            .get("/proc/meminfo")?
+            .iter()
+            .find(|line| line.starts_with("MemTotal:"))
+            .and_then(|line| line.split_whitespace().nth(1))
+            .and_then(|kb| kb.parse::<f32>().ok())
+            .map(|kb| kb / 1024.)
+    }
+
+    /// 报告里展示的服务器信息
+    pub fn summary(&self) -> Vec<(String, String)> {
+        let mut vec = Vec::new();
+        let mut push = |key: &str, value: Option<String>| {
+            if let Some(value) = value {
+                vec.push((key.to_string(), value));
+            }
+        };
+        push("host", self.host.clone());
+        push("OS", self.os.clone());
+        push("nmon version", self.version.clone());
+        push("cpus", self.cpus.clone());
+        push("cpu model", self.cpu_model.clone());
+        push("memory MB", self.mem_total_mb().map(|mb| format!("{:.0}", mb)));
+        push("start", match (&self.date, &self.time) {
+            (Some(date), Some(time)) => Some(format!("{} {}", date, time)),
+            _ => None,
+        });
+        push("interval(s)", self.interval.clone());
+        push("snapshots", self.snapshots.clone());
+        push("command", self.command.clone());
+        vec
+    }
+}
@@ -0,0 +1,304 @@
+use flate2::read::MultiGzDecoder;
+use globset::Glob;
+use globset::GlobSet;
+use globset::GlobSetBuilder;
+use log::debug;
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::error::NmonError;
+use crate::tolerate;
+
+/// nmon数据的来源，按文件扩展名区分，压缩文件和压缩包不需要先解压
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NmonInput {
+    /// app01.nmon
+    Plain(PathBuf),
+    /// app01.nmon.gz
+    Gzip(PathBuf),
+    /// app01.nmon.zst
+    Zstd(PathBuf),
+    /// run.zip，包含多个nmon文件
+    Zip(PathBuf),
+    /// run.tar.gz、run.tgz，包含多个nmon文件
+    TarGz(PathBuf),
+}
+
+impl NmonInput {
+    /// 按扩展名识别，不支持的扩展名返回None
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_string_lossy().to_lowercase();
+        let path = path.to_path_buf();
+        if name.ends_with(".nmon") {
+            Some(NmonInput::Plain(path))
+        } else if name.ends_with(".nmon.gz") {
+            Some(NmonInput::Gzip(path))
+        } else if name.ends_with(".nmon.zst") {
+            Some(NmonInput::Zstd(path))
+        } else if name.ends_with(".zip") {
+            Some(NmonInput::Zip(path))
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(NmonInput::TarGz(path))
+        } else {
+            None
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        match self {
+            NmonInput::Plain(path)
+            | NmonInput::Gzip(path)
+            | NmonInput::Zstd(path)
+            | NmonInput::Zip(path)
+            | NmonInput::TarGz(path) => path,
+        }
+    }
+
+    /// 依次读取其中的每个nmon文件，f的参数为nmon文件名和解压后的reader。
+    /// 压缩文件的nmon文件名去掉.gz、.zst，压缩包里的nmon文件名为: 压缩包文件名/包内路径
+    /// filter: 压缩包里只读取匹配的nmon文件
+    pub fn read_each<F>(&self, filter: &InputFilter, mut f: F) -> Result<(), NmonError>
+    where
+        F: FnMut(&str, &mut dyn BufRead) -> Result<(), NmonError>,
+    {
+        let path = self.path();
+        let io_err = |e: std::io::Error| NmonError::Io { path: path.to_path_buf(), source: e };
+        let filename = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_else(|| path.display().to_string());
+        match self {
+            NmonInput::Plain(_) => {
+                let mut br = BufReader::new(File::open(path).map_err(io_err)?);
+                f(&filename, &mut br)
+            }
+            NmonInput::Gzip(_) => {
+                let mut br = BufReader::new(MultiGzDecoder::new(File::open(path).map_err(io_err)?));
+                f(strip_suffix(&filename, ".gz"), &mut br)
+            }
+            NmonInput::Zstd(_) => {
+                let decoder = zstd::stream::read::Decoder::new(File::open(path).map_err(io_err)?).map_err(io_err)?;
+                let mut br = BufReader::new(decoder);
+                f(strip_suffix(&filename, ".zst"), &mut br)
+            }
+            NmonInput::Zip(_) => {
+                let archive_err = |e: zip::result::ZipError| NmonError::Archive { path: path.to_path_buf(), message: e.to_string() };
+                let mut archive = zip::ZipArchive::new(File::open(path).map_err(io_err)?).map_err(archive_err)?;
+                for i in 0..archive.len() {
+                    let entry = archive.by_index(i).map_err(archive_err)?;
+                    let name = entry.name().to_string();
+                    if !entry.is_file() || !filter.accept_entry(&name) {
+                        continue;
+                    }
+                    debug!("读取{}里的{}", path.display(), name);
+                    let mut br = BufReader::new(entry);
+                    f(&format!("{}/{}", filename, name), &mut br)?;
+                }
+                Ok(())
+            }
+            NmonInput::TarGz(_) => {
+                let mut archive = tar::Archive::new(MultiGzDecoder::new(File::open(path).map_err(io_err)?));
+                for entry in archive.entries().map_err(io_err)? {
+                    let entry = entry.map_err(io_err)?;
+                    if !entry.header().entry_type().is_file() {
+                        continue;
+                    }
+                    let name = entry.path().map_err(io_err)?.to_string_lossy().to_string();
+                    if !filter.accept_entry(&name) {
+                        continue;
+                    }
+                    debug!("读取{}里的{}", path.display(), name);
+                    let mut br = BufReader::new(entry);
+                    f(&format!("{}/{}", filename, name), &mut br)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn strip_suffix<'a>(name: &'a str, suffix: &str) -> &'a str {
+    if name.to_lowercase().ends_with(suffix) {
+        &name[..name.len() - suffix.len()]
+    } else {
+        name
+    }
+}
+
+/// 扫描目录时的过滤条件，glob匹配文件名或相对扫描目录的路径，比如: app*、**/db01/*.nmon.gz
+#[derive(Debug, Clone, Default)]
+pub struct InputFilter {
+    /// 是否扫描子目录
+    recursive: bool,
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+}
+
+impl InputFilter {
+    pub fn new(recursive: bool, include: &[Glob], exclude: &[Glob]) -> Result<Self, NmonError> {
+        Ok(Self {
+            recursive,
+            include: glob_set(include)?,
+            exclude: glob_set(exclude)?,
+        })
+    }
+
+    fn is_match(set: &GlobSet, relative: &Path) -> bool {
+        set.is_match(relative) || relative.file_name().is_some_and(|name| set.is_match(name))
+    }
+
+    /// 没有--include时都包含，--exclude优先
+    fn accept(&self, relative: &Path) -> bool {
+        let included = self.include.as_ref().is_none_or(|set| Self::is_match(set, relative));
+        let excluded = self.exclude.as_ref().is_some_and(|set| Self::is_match(set, relative));
+        included && !excluded
+    }
+
+    /// 压缩包本身只检查--exclude，--include对压缩包里的nmon文件生效
+    fn accept_archive(&self, relative: &Path) -> bool {
+        !self.exclude.as_ref().is_some_and(|set| Self::is_match(set, relative))
+    }
+
+    /// 压缩包里的文件，只读取.nmon文件
+    fn accept_entry(&self, name: &str) -> bool {
+        name.to_lowercase().ends_with(".nmon") && self.accept(Path::new(name))
+    }
+}
+
+fn glob_set(globs: &[Glob]) -> Result<Option<GlobSet>, NmonError> {
+    if globs.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs.iter() {
+        builder.add(glob.clone());
+    }
+    builder
+        .build()
+        .map(Some)
+        .map_err(|e| NmonError::Glob { message: e.to_string() })
+}
+
+/// 从输入的文件和目录中找出所有nmon数据来源，按路径排序。
+/// 直接指定的文件不检查--include、--exclude，扩展名不支持时按未压缩的nmon文件处理
+pub fn discover(paths: &[PathBuf], filter: &InputFilter, lenient: bool, skipped: &mut Vec<NmonError>) -> Result<Vec<NmonInput>, NmonError> {
+    let mut inputs = Vec::new();
+    for p in paths.iter() {
+        if p.is_file() {
+            inputs.push(NmonInput::from_path(p).unwrap_or_else(|| NmonInput::Plain(p.to_path_buf())));
+        } else if p.is_dir() {
+            scan_dir(p, p, filter, lenient, skipped, &mut inputs)?;
+        } else {
+            tolerate(NmonError::InvalidPath { path: p.to_path_buf() }, lenient, skipped)?;
+        }
+    }
+    inputs.sort_by(|a, b| a.path().cmp(b.path()));
+    Ok(inputs)
+}
+
+fn scan_dir(root: &Path, dir: &Path, filter: &InputFilter, lenient: bool, skipped: &mut Vec<NmonError>, inputs: &mut Vec<NmonInput>) -> Result<(), NmonError> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => return tolerate(NmonError::Io { path: dir.to_path_buf(), source: e }, lenient, skipped),
+    };
+    for entry in entries {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                tolerate(NmonError::Io { path: dir.to_path_buf(), source: e }, lenient, skipped)?;
+                continue;
+            }
+        };
+        let path = entry.path();
+        if path.is_dir() {
+            if filter.recursive {
+                scan_dir(root, &path, filter, lenient, skipped, inputs)?;
+            }
+        } else if path.is_file() {
+            let relative = path.strip_prefix(root).unwrap_or(&path);
+            if let Some(input) = NmonInput::from_path(&path) {
+                let accepted = match input {
+                    NmonInput::Zip(_) | NmonInput::TarGz(_) => filter.accept_archive(relative),
+                    _ => filter.accept(relative),
+                };
+                if accepted {
+                    inputs.push(input);
+                } else {
+                    debug!("{}被--include/--exclude过滤", path.display());
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// dir/app01.nmon、dir/notes.txt、dir/run.zip、dir/sub/db01.nmon.gz、dir/sub/deep/web01.nmon、dir/sub/skip.log
+    fn sample_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rnmon_input_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("sub").join("deep")).unwrap();
+        for file in ["app01.nmon", "notes.txt", "run.zip", "sub/db01.nmon.gz", "sub/deep/web01.nmon", "sub/skip.log"] {
+            std::fs::write(dir.join(file), "").unwrap();
+        }
+        dir
+    }
+
+    fn names(dir: &Path, filter: &InputFilter) -> Vec<String> {
+        let mut skipped = Vec::new();
+        discover(&[dir.to_path_buf()], filter, false, &mut skipped)
+            .unwrap()
+            .iter()
+            .map(|input| input.path().strip_prefix(dir).unwrap().to_string_lossy().replace('\\', "/"))
+            .collect()
+    }
+
+    fn globs(globs: &[&str]) -> Vec<Glob> {
+        globs.iter().map(|g| Glob::new(g).unwrap()).collect()
+    }
+
+    #[test]
+    fn recursive_scans_sub_dirs_and_skips_other_files() {
+        let dir = sample_dir("recursive");
+        assert_eq!(names(&dir, &InputFilter::default()), vec!["app01.nmon", "run.zip"]);
+        let filter = InputFilter::new(true, &[], &[]).unwrap();
+        assert_eq!(names(&dir, &filter), vec!["app01.nmon", "run.zip", "sub/db01.nmon.gz", "sub/deep/web01.nmon"]);
+    }
+
+    #[test]
+    fn include_exclude_match_name_or_relative_path() {
+        let dir = sample_dir("glob");
+        // 压缩包不检查--include
+        let filter = InputFilter::new(true, &globs(&["db*"]), &[]).unwrap();
+        assert_eq!(names(&dir, &filter), vec!["run.zip", "sub/db01.nmon.gz"]);
+        let filter = InputFilter::new(true, &[], &globs(&["**/deep/*", "*.zip"])).unwrap();
+        assert_eq!(names(&dir, &filter), vec!["app01.nmon", "sub/db01.nmon.gz"]);
+        let filter = InputFilter::new(true, &globs(&["sub/*"]), &globs(&["*.gz"])).unwrap();
+        assert_eq!(names(&dir, &filter), vec!["run.zip", "sub/deep/web01.nmon"]);
+    }
+
+    #[test]
+    fn archive_entries_only_nmon_files() {
+        let filter = InputFilter::new(false, &globs(&["app*"]), &[]).unwrap();
+        assert!(filter.accept_entry("run/app01.NMON"));
+        assert!(!filter.accept_entry("run/db01.nmon"));
+        assert!(!filter.accept_entry("run/app01.txt"));
+    }
+
+    #[test]
+    fn given_files_are_not_filtered() {
+        let dir = sample_dir("given");
+        let filter = InputFilter::new(false, &[], &globs(&["*.txt"])).unwrap();
+        let mut skipped = Vec::new();
+        let inputs = discover(&[dir.join("notes.txt"), dir.join("sub/db01.nmon.gz")], &filter, false, &mut skipped).unwrap();
+        assert_eq!(inputs, vec![NmonInput::Plain(dir.join("notes.txt")), NmonInput::Gzip(dir.join("sub/db01.nmon.gz"))]);
+
+        let missing = [dir.join("missing.nmon")];
+        assert!(discover(&missing, &filter, false, &mut skipped).is_err());
+        assert!(discover(&missing, &filter, true, &mut skipped).unwrap().is_empty());
+        assert_eq!(skipped.len(), 1);
+    }
+}
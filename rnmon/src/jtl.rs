@@ -0,0 +1,184 @@
+use chrono::DateTime;
+use chrono::FixedOffset;
+use log::debug;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::error::NmonError;
+use crate::tolerate;
+
+/// JMeter结果文件(res.jtl)里的一个请求
+#[derive(Debug, Clone)]
+struct Sample {
+    // 请求开始的时间(毫秒时间戳)
+    timestamp: i64,
+    // 响应时间(毫秒)
+    elapsed: f32,
+    success: bool,
+}
+
+/// JMeter的CSV格式结果文件，需要有列名行(jmeter.save.saveservice.print_field_names=true，默认即为true)，
+/// 以及毫秒时间戳格式的timeStamp列(jmeter.save.saveservice.timestamp_format=ms，默认即为ms)
+#[derive(Debug, Clone)]
+pub struct Jtl {
+    path: PathBuf,
+    // 按timestamp排序
+    samples: Vec<Sample>,
+}
+
+/// 按nmon快照的时间间隔汇总的JMeter指标，与ZZZZ时间序列一一对应，间隔内没有请求时都为0
+#[derive(Debug, Clone, Default)]
+pub struct JtlSeries {
+    pub tps: Vec<f32>,
+    pub mean: Vec<f32>,
+    pub p95: Vec<f32>,
+    pub error: Vec<f32>,
+}
+
+impl Jtl {
+    /// 读取res.jtl，lenient时跳过无法解析的行
+    pub fn read(path: &Path, lenient: bool) -> Result<Self, NmonError> {
+        let jtl_err = |line: u64, message: String| NmonError::Jtl { path: path.to_path_buf(), line: line as usize, message };
+        let mut reader = csv::ReaderBuilder::new()
+            .flexible(true)
+            .from_path(path)
+            .map_err(|e| jtl_err(1, e.to_string()))?;
+        let headers = reader.headers().map_err(|e| jtl_err(1, e.to_string()))?.clone();
+        let index = |name: &str| {
+            headers
+                .iter()
+                .position(|h| h == name)
+                .ok_or_else(|| jtl_err(1, format!("没有{}列，需要CSV格式并保存列名", name)))
+        };
+        let (i_timestamp, i_elapsed, i_success) = (index("timeStamp")?, index("elapsed")?, index("success")?);
+
+        let mut samples = Vec::new();
+        let mut skipped = Vec::new();
+        for record in reader.records() {
+            let record = match record {
+                Ok(record) => record,
+                Err(e) => {
+                    let line = e.position().map(|p| p.line()).unwrap_or_default();
+                    tolerate(jtl_err(line, e.to_string()), lenient, &mut skipped)?;
+                    continue;
+                }
+            };
+            let line = record.position().map(|p| p.line()).unwrap_or_default();
+            // 测试运行中最后一行可能还没写完
+            let (Some(timestamp), Some(elapsed), Some(success)) = (record.get(i_timestamp), record.get(i_elapsed), record.get(i_success)) else {
+                debug!("{}第{}行数据不完整，跳过", path.display(), line);
+                continue;
+            };
+            let sample = match (timestamp.parse::<i64>(), elapsed.parse::<f32>()) {
+                (Ok(timestamp), Ok(elapsed)) => Sample { timestamp, elapsed, success: success == "true" },
+                _ => {
+                    let message = format!("timeStamp'{}'或elapsed'{}'不是数字，timeStamp需为毫秒时间戳", timestamp, elapsed);
+                    tolerate(jtl_err(line, message), lenient, &mut skipped)?;
+                    continue;
+                }
+            };
+            samples.push(sample);
+        }
+        samples.sort_by_key(|s| s.timestamp);
+        debug!("{}共{}个请求", path.display(), samples.len());
+        Ok(Self { path: path.to_path_buf(), samples })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 是否有请求在zzzz时间范围内，时间不重叠时通常是--tz不正确
+    pub fn overlaps(&self, zzzz: &[DateTime<FixedOffset>]) -> bool {
+        let (Some(first), Some(last)) = (zzzz.first(), zzzz.last()) else {
+            return false;
+        };
+        let (first, last) = (first.timestamp_millis(), last.timestamp_millis());
+        self.samples.iter().any(|s| s.timestamp >= first && s.timestamp <= last)
+    }
+
+    /// 按nmon快照的时间间隔汇总: 第i个快照统计(zzzz[i-1], zzzz[i]]内开始的请求，
+    /// 第一个快照的间隔按第二个快照的间隔往前推算。
+    /// tps为每秒请求数，mean、p95为响应时间(毫秒)，error为失败请求的百分比
+    pub fn series(&self, zzzz: &[DateTime<FixedOffset>]) -> JtlSeries {
+        let mut series = JtlSeries::default();
+        for (i, end) in zzzz.iter().enumerate() {
+            let end = end.timestamp_millis();
+            let start = match i {
+                0 => zzzz.get(1).map(|next| 2 * end - next.timestamp_millis()).unwrap_or(end),
+                _ => zzzz[i - 1].timestamp_millis(),
+            };
+            let from = self.samples.partition_point(|s| s.timestamp <= start);
+            let to = self.samples.partition_point(|s| s.timestamp <= end);
+            let samples = &self.samples[from..to.max(from)];
+            if samples.is_empty() || end <= start {
+                series.tps.push(0.);
+                series.mean.push(0.);
+                series.p95.push(0.);
+                series.error.push(0.);
+                continue;
+            }
+            let count = samples.len() as f32;
+            let mut elapsed: Vec<f32> = samples.iter().map(|s| s.elapsed).collect();
+            elapsed.sort_by(|a, b| a.total_cmp(b));
+            let p95 = elapsed[((count * 0.95).ceil() as usize).clamp(1, elapsed.len()) - 1];
+            series.tps.push(count * 1000. / (end - start) as f32);
+            series.mean.push(elapsed.iter().sum::<f32>() / count);
+            series.p95.push(p95);
+            series.error.push(samples.iter().filter(|s| !s.success).count() as f32 * 100. / count);
+        }
+        series
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn zzzz(seconds: &[i64]) -> Vec<DateTime<FixedOffset>> {
+        let tz = FixedOffset::east_opt(8 * 3600).unwrap();
+        seconds.iter().map(|s| tz.timestamp_opt(*s, 0).unwrap()).collect()
+    }
+
+    fn jtl(samples: &[(i64, f32, bool)]) -> Jtl {
+        let samples = samples.iter().map(|&(timestamp, elapsed, success)| Sample { timestamp, elapsed, success }).collect();
+        Jtl { path: PathBuf::from("res.jtl"), samples }
+    }
+
+    #[test]
+    fn bucket_is_left_open_right_closed() {
+        // 第一个间隔按第二个间隔往前推算为(0s, 10s]
+        let jtl = jtl(&[(0, 999., false), (5_000, 100., true), (10_000, 300., false), (10_001, 50., true), (20_000, 80., true), (30_001, 999., false)]);
+        let series = jtl.series(&zzzz(&[10, 20, 30]));
+        assert_eq!(series.tps, vec![0.2, 0.2, 0.]);
+        assert_eq!(series.mean, vec![200., 65., 0.]);
+        assert_eq!(series.p95, vec![300., 80., 0.]);
+        assert_eq!(series.error, vec![50., 0., 0.]);
+    }
+
+    #[test]
+    fn error_rate_and_tps_use_nmon_interval() {
+        // nmon间隔为5秒，5秒内20个请求，其中3个失败
+        let samples: Vec<(i64, f32, bool)> = (0..20).map(|i| (60_000 + 250 * i + 1, 10., i % 7 != 0)).collect();
+        let series = jtl(&samples).series(&zzzz(&[60, 65]));
+        assert_eq!(series.tps, vec![0., 4.]);
+        assert_eq!(series.error, vec![0., 15.]);
+        assert!(jtl(&samples).overlaps(&zzzz(&[60, 65])));
+        assert!(!jtl(&samples).overlaps(&zzzz(&[0, 5])));
+    }
+
+    #[test]
+    fn read_skips_bad_rows_when_lenient() {
+        let path = std::env::temp_dir().join(format!("rnmon_jtl_{}.jtl", std::process::id()));
+        std::fs::write(
+            &path,
+            "timeStamp,elapsed,label,success\n1000,10,a,true\nbad,20,a,true\n3000,30,a,false\n4000,40",
+        )
+        .unwrap();
+        assert!(matches!(Jtl::read(&path, false), Err(NmonError::Jtl { line: 3, .. })));
+        let jtl = Jtl::read(&path, true).unwrap();
+        assert_eq!(jtl.samples.len(), 2);
+        assert!(!jtl.samples[1].success);
+    }
+}
@@ -0,0 +1,1645 @@
+//! 解析nmon文件，计算各指标的统计值，生成HTML图表。
+//!
+//! 除了rnmon命令行程序，其他程序也可以直接使用本库分析nmon文件，比如:
+//!
+//! ```no_run
+//! use std::collections::HashSet;
+//! use rnmon::{NmonData, ParseOptions, ReportOptions};
+//!
+//! let sections: HashSet<String> = ["CPU_ALL", "MEM", "DISKXFER"].iter().map(|s| s.to_string()).collect();
+//! let ndata = NmonData::new("app01.nmon".as_ref(), &sections, &ParseOptions::default()).unwrap();
+//! let cpu_all = ndata.measurement("CPU_ALL").unwrap();
+//! println!("{:?} {:?}", cpu_all.header(), cpu_all.rows_quantile(0.95));
+//!
+//! // 用ReportOptions指定图表，生成HTML报告
+//! let options = ReportOptions { output: "./report".into(), ..Default::default() };
+//! rnmon::render_html(&[ndata], &options).unwrap();
+//! ```
+//!
+//! 命令行参数(clap)、控制台输出、watch和serve等只供rnmon命令行程序使用，不属于库的接口。
+
+pub(crate) mod askama;
+pub(crate) mod baseline;
+pub(crate) mod chart;
+pub(crate) mod downsample;
+pub(crate) mod error;
+pub(crate) mod export;
+pub(crate) mod host;
+pub(crate) mod input;
+pub(crate) mod jtl;
+pub(crate) mod output;
+pub(crate) mod report;
+pub(crate) mod rule;
+pub(crate) mod section;
+pub(crate) mod serve;
+mod stats;
+pub(crate) mod top;
+pub(crate) mod tz;
+pub(crate) mod watch;
+pub(crate) mod window;
+#[doc(hidden)]
+pub mod cli;
+
+use clap::{ArgAction, Parser};
+use log::debug;
+use log::trace;
+use log::warn;
+use anyhow::Result;
+use std::path::PathBuf;
+use std::path::Path;
+use std::collections::HashSet;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::str::FromStr;
+use chrono::DateTime;
+use chrono::NaiveDateTime;
+use chrono::FixedOffset;
+use ndarray::Array1;
+use ndarray::Array2;
+use ndarray::Axis;
+use ndarray::ShapeBuilder;
+use ndarray_stats::QuantileExt;
+use ndarray_stats::interpolate::Linear;
+use noisy_float::types::n64;
+use regex::Regex;
+use globset::Glob;
+
+use crate::askama::Point;
+use crate::askama::POINT_X_FORMAT;
+use crate::askama::html::Chart;
+pub use crate::askama::html::ChartsName;
+use crate::askama::js::JsCache;
+use crate::askama::js::EchartsOption;
+pub use crate::chart::ChartKind;
+pub use crate::chart::ChartSpec;
+pub use crate::error::NmonError;
+pub use crate::host::HostInfo;
+pub use crate::host::Platform;
+pub use crate::input::InputFilter;
+pub use crate::input::NmonInput;
+pub use crate::jtl::Jtl;
+pub use crate::report::ChartLabel;
+pub use crate::report::Report;
+pub use crate::rule::Rule;
+pub use crate::rule::RuleResult;
+pub use crate::rule::evaluate as evaluate_rules;
+pub use crate::section::SectionInfo;
+pub use crate::section::scan as scan_sections;
+use crate::section::FileSections;
+use crate::section::SectionMatcher;
+use crate::section::CPU_SECTIONS;
+use crate::top::TopBuilder;
+pub use crate::top::TopProcesses;
+pub use crate::tz::FileTz;
+pub use crate::tz::NmonTz;
+pub use crate::window::TimeBound;
+pub use crate::window::TimeWindow;
+
+#[derive(Parser, Debug, Clone)]
+pub(crate) struct NmonArgs {
+    /// 1.当输入为后缀为.nmon的文件时，分析该文件。
+    /// 2.当输入为目录时，自动分析该目录下文件后缀为.nmon的文件。
+    /// 3.支持.nmon.gz、.nmon.zst压缩文件，以及包含nmon文件的.zip、.tar.gz压缩包，不需要先解压。
+    path: Vec<PathBuf>,
+    /// HTML图表输出目录。若该目录已存在，则清除该目录，并重新生成。
+    /// 该参数只在nmon子命令时才生效，run子命令时，该参数值与run子命令的outputfolder相同
+    #[arg(long, default_value = "./")]
+    html_output: Option<PathBuf>,
+    #[command(flatten)]
+    run_nmon_args: RunNmonArgs,
+    /// watch时HTML报告自动刷新数据的间隔(秒)
+    #[arg(skip)]
+    live: Option<u64>,
+}
+
+impl NmonArgs {
+    pub fn new(nmon_path: Vec<PathBuf>, html_output: PathBuf, run_nmon_args: RunNmonArgs) -> Self {
+        Self {
+            path: nmon_path,
+            html_output: Some(html_output),
+            live: None,
+            run_nmon_args
+        }
+    }
+    /// --rule和--rules-file里的所有阈值规则
+    pub fn rules(&self) -> Result<Vec<Rule>, String> {
+        let mut rules = self.run_nmon_args.rule.clone();
+        if let Some(ref rules_file) = self.run_nmon_args.rules_file {
+            rules.extend(rule::read_rules(rules_file)?);
+        }
+        Ok(rules)
+    }
+    pub fn run_nmon_args(&self) -> &RunNmonArgs {
+        &self.run_nmon_args
+    }
+    /// watch时生成HTML报告，报告每refresh秒重新加载图表数据
+    pub fn set_live(&mut self, refresh: u64) {
+        self.live = Some(refresh);
+        self.run_nmon_args.html = true;
+    }
+    pub fn html_output_folder(&self) -> PathBuf {
+        self.html_output.clone().unwrap()
+    }
+    pub fn metrics_name(&self) -> HashSet<String> {
+        // 设置要从nmon文件在处理的指标名称
+        // 第四版要求 CPUXX、CPU_ALL、DISKXFER、DISKBUSY、DISKREAD、DISKWRITE、DISKXFER、DISKBSIZE必填输入
+        let mut cell = HashSet::new();
+        // 有报告定义文件时，只解析报告里的图表需要的指标
+        if let Some(ref report) = self.run_nmon_args.report {
+            cell.extend(report.sections());
+            for spec in self.run_nmon_args.chart.iter() {
+                cell.insert(spec.section().to_string());
+            }
+            if self.run_nmon_args.jtl.is_some() {
+                cell.extend(ChartsName::JTL.sections());
+            }
+            return cell;
+        }
+        if self.run_nmon_args.cpu || self.run_nmon_args.html {
+            cell.insert(CPU_SECTIONS.into());
+            cell.insert("CPU_ALL".into());
+            // nmon system_summary
+            cell.insert("DISKXFER".into());
+            // AIX LPAR的物理CPU和授权(entitlement)
+            cell.insert("LPAR".into());
+        }
+        if self.run_nmon_args.disk_io {
+            cell.insert("DISKXFER".into());
+        }
+        if self.run_nmon_args.disk_summary {
+            cell.insert("DISKREAD".into());
+            cell.insert("DISKWRITE".into());
+            cell.insert("DISKBSIZE".into());
+        }
+        if self.run_nmon_args.disk_busy {
+            cell.insert("DISKBUSY".into());
+        }
+        if self.run_nmon_args.mem_free || self.run_nmon_args.mem_active || self.run_nmon_args.mem_swap {
+            cell.insert("MEM".into());
+            // AIX
+            cell.insert("MEMNEW".into());
+            cell.insert("MEMUSE".into());
+        }
+        if self.run_nmon_args.jfsfile {
+            cell.insert("JFSFILE".into());
+        }
+        if self.run_nmon_args.disk_io {
+            cell.insert("DISKXFER".into());
+        }
+        if self.run_nmon_args.disk_summary {
+            cell.insert("DISKXFER".into());
+        }
+        if self.run_nmon_args.net {
+            cell.insert("NET".into());
+            cell.insert("NETPACKET".into());
+        }
+        if self.run_nmon_args.top {
+            cell.insert("TOP".into());
+            cell.insert("UARG".into());
+        }
+        if self.run_nmon_args.vm {
+            cell.insert("VM".into());
+            // AIX
+            cell.insert("PAGE".into());
+        }
+        if self.run_nmon_args.proc {
+            cell.insert("PROC".into());
+        }
+        for spec in self.run_nmon_args.chart.iter() {
+            cell.insert(spec.section().to_string());
+        }
+        if self.run_nmon_args.jtl.is_some() {
+            cell.insert("CPU_ALL".into());
+            cell.insert("MEM".into());
+            cell.insert("MEMNEW".into());
+            cell.insert("DISKBUSY".into());
+        }
+        if self.run_nmon_args.compare {
+            cell.insert("CPU_ALL".into());
+            cell.insert("MEM".into());
+            cell.insert("DISKBUSY".into());
+            cell.insert("NET".into());
+        }
+
+        cell
+    }
+
+    /// 返回解析成功的nmon数据，以及--lenient时被跳过的文件或目录的错误
+    pub fn nmon_dates(&self, cell: &HashSet<String>) -> Result<(Vec<NmonData>, Vec<NmonError>), NmonError> {
+        let lenient = self.run_nmon_args.lenient;
+        let mut skipped = Vec::new();
+        let filter = InputFilter::new(self.run_nmon_args.recursive, &self.run_nmon_args.include, &self.run_nmon_args.exclude)?;
+        let inputs = input::discover(&self.path, &filter, lenient, &mut skipped)?;
+        let mut options = ParseOptions {
+            disk_filter: self.run_nmon_args.disk_filter.clone(),
+            lenient,
+            tz: NmonTz::default(),
+            window: TimeWindow::new(self.run_nmon_args.from.clone(), self.run_nmon_args.to.clone()),
+            max_points: self.run_nmon_args.max_points.map(|n| n as usize),
+        };
+        let mut res = Vec::new();
+        for input in inputs.iter() {
+            // 压缩包里的nmon文件读取失败时，跳过该nmon文件，继续读取压缩包里的其他nmon文件
+            let read = input.read_each(&filter, |filename, br| {
+                options.tz = FileTz::select(&self.run_nmon_args.tz_file, Path::new(filename), &self.run_nmon_args.tz).clone();
+                debug!("{}文件，ZZZZ时间序列的时区: {:?}", filename, options.tz);
+                match NmonData::from_reader(br, filename, cell, &options) {
+                    Ok(ndata) => res.push(ndata),
+                    Err(e) => tolerate(e, lenient, &mut skipped)?,
+                }
+                Ok(())
+            });
+            if let Err(e) = read {
+                tolerate(e, lenient, &mut skipped)?;
+            }
+        }
+        Ok((res, skipped))
+    }
+
+    /// 扫描各nmon文件里的所有指标，返回(nmon文件名, 各指标, ZZZZ快照数)，以及--lenient时被跳过的文件或目录的错误
+    pub fn list_sections(&self) -> Result<(Vec<FileSections>, Vec<NmonError>), NmonError> {
+        let lenient = self.run_nmon_args.lenient;
+        let mut skipped = Vec::new();
+        let filter = InputFilter::new(self.run_nmon_args.recursive, &self.run_nmon_args.include, &self.run_nmon_args.exclude)?;
+        let inputs = input::discover(&self.path, &filter, lenient, &mut skipped)?;
+        let mut res = Vec::new();
+        for input in inputs.iter() {
+            let read = input.read_each(&filter, |filename, br| {
+                match section::scan(br, filename) {
+                    Ok((sections, snapshots)) => res.push((filename.to_string(), sections, snapshots)),
+                    Err(e) => tolerate(e, lenient, &mut skipped)?,
+                }
+                Ok(())
+            });
+            if let Err(e) = read {
+                tolerate(e, lenient, &mut skipped)?;
+            }
+        }
+        Ok((res, skipped))
+    }
+
+    /// 按命令行参数选择HTML报告的图表，path: 报告的输出目录
+    pub(crate) fn report_options(&self, nmon_datas: &[NmonData], path: PathBuf) -> Result<ReportOptions, NmonError> {
+        // 生成有哪些可选择的nmon监控指标
+        let mut charts = Vec::new();
+        if let Some(ref report) = self.run_nmon_args.report {
+            charts = report.charts();
+            // 没有--jtl时不生成JTL图表，有--jtl但报告里没有时追加
+            let has_jtl = charts.iter().any(|(name, _)| matches!(name, ChartsName::JTL));
+            if self.run_nmon_args.jtl.is_none() {
+                charts.retain(|(name, _)| !matches!(name, ChartsName::JTL));
+            } else if !has_jtl {
+                charts.push((ChartsName::JTL, true));
+            }
+            for spec in self.run_nmon_args.chart.iter() {
+                charts.push((ChartsName::SPEC(spec.clone()), false));
+            }
+        } else if self.run_nmon_args.html {
+            if self.run_nmon_args.cpu || self.run_nmon_args.html {
+                charts.push((ChartsName::SYS_SUMM, true));
+                charts.push((ChartsName::CPU_SUMM, false));
+                charts.push((ChartsName::CPU_ALL, false));
+            }
+            if self.run_nmon_args.jfsfile {
+                charts.push((ChartsName::JFSFILE, false));
+            }
+            if self.run_nmon_args.mem_free {
+                charts.push((ChartsName::MEM_FREE, false));
+            }
+            if self.run_nmon_args.mem_active {
+                charts.push((ChartsName::MEM_ACTIVE, false));
+            }
+            if self.run_nmon_args.mem_swap {
+                charts.push((ChartsName::MEM_SWAP, false));
+            }
+            if self.run_nmon_args.disk_busy {
+                charts.push((ChartsName::DISKBUSY_AWMN, false));
+                charts.push((ChartsName::DISKBUSY_OVERTIME, false));
+            }
+            if self.run_nmon_args.disk_summary {
+                charts.push((ChartsName::DISK_SUMM, false));
+                charts.push((ChartsName::DISKREAD_AWMN, false));
+                charts.push((ChartsName::DISKWRITE_AWMN, false));
+                charts.push((ChartsName::DISKBSIZE_AWMN, false));
+            }
+            if self.run_nmon_args.disk_io {
+                charts.push((ChartsName::DISKXFER_AWMN, false));
+            }
+            if self.run_nmon_args.net {
+                charts.push((ChartsName::NET_TOTAL, false));
+                charts.push((ChartsName::NET_DEVICE, false));
+                charts.push((ChartsName::NET_PACKET, false));
+            }
+            if self.run_nmon_args.top {
+                charts.push((ChartsName::TOP_CPU, false));
+                charts.push((ChartsName::TOP_RESDATA, false));
+            }
+            if self.run_nmon_args.vm {
+                charts.push((ChartsName::VM_PAGING, false));
+            }
+            if self.run_nmon_args.proc {
+                charts.push((ChartsName::PROC_RUNQUEUE, false));
+                charts.push((ChartsName::PROC_RATES, false));
+            }
+            if self.run_nmon_args.jtl.is_some() {
+                charts.push((ChartsName::JTL, true));
+            }
+            for spec in self.run_nmon_args.chart.iter() {
+                charts.push((ChartsName::SPEC(spec.clone()), false));
+            }
+            // 有AIX的nmon文件时才生成AIX的图表
+            if nmon_datas.iter().any(|ndata| ndata.host().platform() == Platform::Aix) {
+                if self.run_nmon_args.cpu || self.run_nmon_args.html {
+                    charts.push((ChartsName::AIX_LPAR, false));
+                }
+                if self.run_nmon_args.mem_free || self.run_nmon_args.mem_active || self.run_nmon_args.mem_swap {
+                    charts.push((ChartsName::AIX_MEMNEW, false));
+                    charts.push((ChartsName::AIX_MEMUSE, false));
+                }
+                if self.run_nmon_args.vm {
+                    charts.push((ChartsName::AIX_PAGE, false));
+                }
+            }
+        }
+
+        // 多个nmon文件的对比图
+        let mut compares = Vec::new();
+        if let Some(ref report) = self.run_nmon_args.report {
+            compares = report.compares();
+        } else if self.run_nmon_args.compare {
+            compares.push((ChartsName::CMP_CPU, true));
+            compares.push((ChartsName::CMP_MEM_FREE, false));
+            compares.push((ChartsName::CMP_DISKBUSY, false));
+            compares.push((ChartsName::CMP_NET, false));
+        }
+
+        // JMeter的结果文件，watch时每次重新读取
+        let jtl = match self.run_nmon_args.jtl {
+            Some(ref path) => Some(Jtl::read(path, self.run_nmon_args.lenient)?),
+            None => None,
+        };
+        Ok(ReportOptions {
+            output: path,
+            charts,
+            compares,
+            labels: self.run_nmon_args.report.as_ref().map(|report| report.labels().clone()).unwrap_or_default(),
+            disk_top: self.run_nmon_args.disk_top,
+            top_num: self.run_nmon_args.top_num,
+            jtl,
+            title: self.run_nmon_args.report.as_ref().and_then(|report| report.title()).map(|title| title.to_string()),
+            single_file: self.run_nmon_args.single_file,
+            compress_data: self.run_nmon_args.compress_data,
+            refresh: self.live,
+        })
+    }
+}
+
+
+#[derive(Parser, Debug, Clone)]
+pub(crate) struct RunNmonArgs {
+    /// 对输入的nmon文件进行分析，并生成HTML图表，当指定html时cpu默认为true
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub html: bool,
+
+    /// 分析nmon文件时，设置对cpu使用进行分析
+    #[arg(long, default_value_t = true)]
+    pub cpu: bool,
+
+    /// 分析nmon文件时，设置对mem使用进行分析
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub mem_free: bool,
+
+    /// 分析nmon文件时，设置对mem使用进行分析
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub mem_active: bool,
+
+    /// 分析nmon文件时，设置对mem使用进行分析
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub mem_swap: bool,
+
+    /// 分析nmon文件时，设置对jfsfile使用进行分析
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub jfsfile: bool,
+
+    /// 分析nmon文件时，设置对disk_busy使用进行分析
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub disk_busy: bool,
+
+    /// DISKBUSY随时间变化的图表中，只显示按WAvg.排序最繁忙的前N个磁盘
+    #[arg(long, value_name = "N", default_value_t = 10)]
+    pub disk_top: usize,
+
+    /// 只保留磁盘名称匹配REGEX的磁盘，对所有DISK开头的指标生效(DISKBUSY、DISKREAD、DISKWRITE、DISKXFER、DISKBSIZE等)，
+    /// 比如: --disk-filter "^(sd[a-z]+|nvme\d+n\d+)$" 只保留整盘，忽略分区和dm-设备
+    #[arg(long, value_name = "REGEX")]
+    pub disk_filter: Option<Regex>,
+
+    /// 分析nmon文件时，设置对DISKXFER使用进行分析，即各磁盘的IO/sec
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub disk_io: bool,
+
+    /// 分析nmon文件时，设置对DISK_SUMM使用进行分析，包括DISKREAD、DISKWRITE、DISKXFER、DISKBSIZE
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub disk_summary: bool,
+
+    /// 分析nmon文件时，设置对网络使用进行分析，包括NET(各网卡及总的读写KB/s)和NETPACKET(各网卡读写包数/s)
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub net: bool,
+
+    /// 分析nmon文件时，设置对TOP进程(nmon -t)进行分析，按命令名称汇总各进程的%CPU和ResData
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub top: bool,
+
+    /// TOP进程的图表和表格中，只显示按%CPU或ResData均值排序的前N个命令
+    #[arg(long, value_name = "N", default_value_t = 10)]
+    pub top_num: usize,
+
+    /// 分析nmon文件时，设置对VM使用进行分析，包括pgpgin、pgpgout、pswpin、pswpout、pgmajfault
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub vm: bool,
+
+    /// 分析nmon文件时，设置对PROC使用进行分析，包括运行队列(Runnable)与CPU数量对比、Blocked、pswitch、syscall、fork
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub proc: bool,
+
+    /// 生成HTML图表时，增加多个nmon文件(服务器)的对比图，同一指标每个服务器一条线，包括CPU使用率、memfree、DISKBUSY最大值、网络读写KB/s
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub compare: bool,
+
+    /// JMeter的结果文件(res.jtl)，生成HTML图表时增加JTL图表，每个服务器的TPS、平均/p95响应时间、错误率与CPU、内存、
+    /// DISKBUSY在同一时间轴上对照，缩放时各服务器的JTL图表同步。JTL的时间戳与nmon的ZZZZ按--tz对齐
+    #[arg(long, value_name = "FILE", requires = "html")]
+    pub jtl: Option<PathBuf>,
+
+    /// 自定义图表，可指定多次，格式为: "SECTION:COLUMN[,COLUMN...][:KIND]"，可以是nmon文件里的任意指标和列，
+    /// COLUMN可以是glob(*为所有列)，KIND为line(默认)、area(堆叠)、sum(各列之和)、awmn(各列的Avg.、WAvg.、Max.、Min.)，
+    /// 比如: --chart "MEM:memfree,cached,buffers:line" --chart "DISKBUSY:*:awmn"
+    #[arg(long, value_name = "SPEC", action = ArgAction::Append, requires = "html")]
+    pub chart: Vec<ChartSpec>,
+
+    /// 报告定义文件(TOML，后缀为.json时为JSON)，指定生成哪些图表、图表的顺序、标题和单位，以及额外需要解析的指标。
+    /// 指定时HTML报告只包含文件里定义的图表，--cpu、--mem-free等选择图表的参数不再生效，--chart的图表追加在后面
+    #[arg(long, value_name = "FILE", value_parser = Report::read, requires = "html")]
+    pub report: Option<Report>,
+
+    /// 阈值规则，可指定多次，格式为: "SECTION:COLUMN[+COLUMN...] STAT OP VALUE"，STAT为mean、max、min、wavg、stdev，OP为<、<=、>、>=，
+    /// COLUMN为*时各列分别判断，比如: --rule "CPU_ALL:User%+Sys% mean < 70" --rule "MEM:memfree min > 1024" --rule "DISKBUSY:* max < 90"。
+    /// 有规则不通过时退出码为3
+    #[arg(long, value_name = "RULE", action = ArgAction::Append)]
+    pub rule: Vec<Rule>,
+
+    /// 阈值规则文件，每行一个规则，格式同--rule，忽略空行和#开头的注释
+    #[arg(long, value_name = "FILE")]
+    pub rules_file: Option<PathBuf>,
+
+    /// nmon文件里ZZZZ时间序列所在的时区(即服务器的时区)，默认为运行rnmon的机器的时区。
+    /// 可以是固定时区(+08:00、-0500、UTC)或IANA时区名称(Asia/Shanghai)
+    #[arg(long, value_name = "TZ", default_value = "local")]
+    pub tz: NmonTz,
+
+    /// 单独指定某些nmon文件的时区，格式为: REGEX=TZ，REGEX匹配nmon文件名，可指定多次，比如: --tz-file "^db01=UTC"
+    #[arg(long, value_name = "REGEX=TZ", action = ArgAction::Append)]
+    pub tz_file: Vec<FileTz>,
+
+    /// 只分析该时间之后的数据，可以是相对nmon文件开始(+)或结束(-)的偏移量，比如: +60s、+5m、-30s，
+    /// 也可以是时间，比如: "2020-06-16 17:41:00"、2020-06-16T17:41:00+08:00、17:41:00(日期为nmon文件开始的日期)
+    #[arg(long, value_name = "TIME", allow_hyphen_values = true)]
+    pub from: Option<TimeBound>,
+
+    /// 只分析该时间之前的数据，格式同--from，比如: --from +60s --to -30s 去掉开始60秒和最后30秒的数据
+    #[arg(long, value_name = "TIME", allow_hyphen_values = true)]
+    pub to: Option<TimeBound>,
+
+    /// 宽松模式，遇到无法解析的数据(非数字、时间序列错误、文件读取失败等)时跳过该数据或文件，而不是报错退出，
+    /// 最后打印各nmon文件跳过的数据
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub lenient: bool,
+
+    /// 生成HTML图表时，把echarts库和图表数据内嵌到一个index_nmons.html文件里，方便邮件发送或附加到工单
+    #[arg(long, action = ArgAction::SetTrue, requires = "html")]
+    pub single_file: bool,
+
+    /// --single-file时，图表数据gzip压缩后以base64内嵌，文件更小，需浏览器支持DecompressionStream
+    #[arg(long, action = ArgAction::SetTrue, requires = "single_file")]
+    pub compress_data: bool,
+
+    /// 生成HTML图表时，每条曲线最多的点数，超过时用LTTB(Largest-Triangle-Three-Buckets)降采样，保留峰值。
+    /// 多天的nmon文件数据点很多时，避免图表数据文件过大导致浏览器卡死，比如: --max-points 2000
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(3..))]
+    pub max_points: Option<u64>,
+
+    /// 输入为目录时，同时扫描子目录里的nmon文件
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub recursive: bool,
+
+    /// 输入为目录或压缩包时，只读取匹配glob的nmon文件，glob匹配文件名或相对路径，可以指定多次，比如: --include 'app*'
+    #[arg(long, value_name = "GLOB")]
+    pub include: Vec<Glob>,
+
+    /// 输入为目录或压缩包时，跳过匹配glob的nmon文件，优先于--include，可以指定多次，比如: --exclude '**/old/**'
+    #[arg(long, value_name = "GLOB")]
+    pub exclude: Vec<Glob>,
+}
+
+impl Default for RunNmonArgs {
+    /// 与命令行不指定任何参数时相同
+    fn default() -> Self {
+        Self::parse_from(["rnmon"])
+    }
+}
+
+
+/// 生成报告: 控制台及res.nmon.txt里的服务器信息和统计值、res.nmon.md，--html时生成HTML图表
+/// path: 报告的输出目录
+pub(crate) fn nmon(
+    nmon_datas: &[NmonData],
+    // cell: &HashSet<String>,
+    path: PathBuf,
+    nmonargs: &NmonArgs,
+) -> Result<()> {
+
+    // 打印CPU_ALL到控制台
+    let mut res_nmon_txt = String::new();
+    for ndata in nmon_datas.iter() {
+        // 打印nmon文件头里的服务器信息
+        let res = output::console_print_host(ndata);
+        res_nmon_txt.push_str(res.as_str());
+        res_nmon_txt.push('\n');
+        // 使用表格形式，打印各指标各列的统计值到屏蔽，包括： min, max, mean, wavg, stdev, p50, p90, p95, p99
+        let res = output::console_print_summary(ndata);
+        res_nmon_txt.push_str(res.as_str());
+        res_nmon_txt.push('\n');
+    }
+    // 打印磁盘读写合计到控制台
+    if nmonargs.run_nmon_args.disk_summary {
+        for ndata in nmon_datas.iter() {
+            let res = output::console_print_disk_summ(ndata);
+            res_nmon_txt.push_str(res.as_str());
+            res_nmon_txt.push('\n');
+        }
+    }
+    // 打印TOP进程到控制台
+    if nmonargs.run_nmon_args.top {
+        for ndata in nmon_datas.iter() {
+            let res = output::console_print_top(ndata, nmonargs.run_nmon_args.top_num);
+            res_nmon_txt.push_str(res.as_str());
+            res_nmon_txt.push('\n');
+        }
+    }
+    // 保存console输出到文件
+    output::save(path.join("res.nmon.txt"), res_nmon_txt);
+    // 保存Markdown格式的统计值，方便粘贴到测试报告里
+    output::save(path.join("res.nmon.md"), output::markdown_summary(nmon_datas));
+
+    if nmonargs.run_nmon_args.html {
+        render_html(nmon_datas, &nmonargs.report_options(nmon_datas, path)?)?;
+    }
+
+    Ok(())
+}
+
+/// 生成HTML报告的选项，不依赖命令行参数，作为库使用时直接构造，再调用[`render_html`]
+#[derive(Debug, Clone)]
+pub struct ReportOptions {
+    /// 报告的输出目录，不存在时自动创建
+    pub output: PathBuf,
+    /// 各nmon文件的图表，以及是否默认选中，按顺序显示
+    pub charts: Vec<(ChartsName, bool)>,
+    /// 多个nmon文件的对比图，以及是否默认选中
+    pub compares: Vec<(ChartsName, bool)>,
+    /// 内置图表的标题和Y轴的单位，覆盖默认的标题和单位，key为ChartsName::id()
+    pub labels: BTreeMap<String, ChartLabel>,
+    /// DISKBUSY_OVERTIME图表只显示最繁忙的前N个磁盘
+    pub disk_top: usize,
+    /// TOP_CPU、TOP_RESDATA图表只显示前N个命令
+    pub top_num: usize,
+    /// JTL图表的JMeter结果，没有时不生成JTL图表
+    pub jtl: Option<Jtl>,
+    /// 报告的标题
+    pub title: Option<String>,
+    /// echarts库和图表数据内嵌到index_nmons.html里
+    pub single_file: bool,
+    /// single_file时图表数据gzip压缩后以base64内嵌
+    pub compress_data: bool,
+    /// 报告页面每refresh秒重新加载图表数据(watch)，此时不内嵌图表数据
+    pub refresh: Option<u64>,
+}
+
+impl Default for ReportOptions {
+    /// 与rnmon --html相同的默认图表
+    fn default() -> Self {
+        Self {
+            output: PathBuf::from("./"),
+            charts: vec![(ChartsName::SYS_SUMM, true), (ChartsName::CPU_SUMM, false), (ChartsName::CPU_ALL, false)],
+            compares: Vec::new(),
+            labels: BTreeMap::new(),
+            disk_top: 10,
+            top_num: 10,
+            jtl: None,
+            title: None,
+            single_file: false,
+            compress_data: false,
+            refresh: None,
+        }
+    }
+}
+
+/// 生成HTML报告: index_nmons.html，以及图表数据index_nmons_data.js和echarts.min.js(single_file时内嵌到html里)
+pub fn render_html(nmon_datas: &[NmonData], options: &ReportOptions) -> Result<(), NmonError> {
+    let path = &options.output;
+    let write = |file: PathBuf, data: String| std::fs::write(&file, data).map_err(|e| NmonError::Io { path: file, source: e });
+    std::fs::create_dir_all(path).map_err(|e| NmonError::Io { path: path.clone(), source: e })?;
+
+    let mut html_template_data = Vec::new();
+    let mut js_options = Vec::new();
+    // 生成HTML模板数据
+    for (i, ndata) in nmon_datas.iter().enumerate() {
+        // 图表的元素<div>的id由3位数据的nmon数据文件id+图表名称组成,比如: 001_SYS_SUMM
+        let nmon_id = format!("{:03}", i);
+        html_template_data.push((nmon_id.clone(), ndata.filename().to_string(), ndata.host().summary()));
+
+        for (name, _) in options.charts.iter() {
+            let chart_id = format!("{}_{}", nmon_id, name.id());
+            // 报告定义文件里指定的标题和单位，在生成的图表上覆盖
+            let label = options.labels.get(&name.id());
+            let labeled = |option: String| {
+                let option = EchartsOption::new(chart_id.clone(), option);
+                match label {
+                    Some(label) => option.with_label(label.title.as_ref().map(|title| format!("{} {}", title, ndata.filename())), label.unit.clone()),
+                    None => option,
+                }
+            };
+            // 图表不适用于该nmon文件的平台时，显示不适用，而不是空白的图表
+            if name.platform().is_some_and(|platform| platform != ndata.host().platform()) {
+                js_options.push(labeled(askama::js_not_applicable(name, ndata)));
+                continue;
+            }
+            match name {
+                ChartsName::SYS_SUMM => {
+                    let system_summary = askama::js_system_summary(ndata);
+                    js_options.push(labeled(system_summary));
+                },
+                ChartsName::CPU_SUMM => {
+                    let cpu_summary = askama::js_cpu_summ(ndata);
+                    js_options.push(labeled(cpu_summary));
+                },
+                ChartsName::CPU_ALL => {
+                    let cpu_all = askama::js_cpu_all(ndata);
+                    js_options.push(labeled(cpu_all));
+                },
+                ChartsName::JFSFILE => {
+                    let jfsfile = askama::js_jfsfile(ndata);
+                    js_options.push(labeled(jfsfile));
+                },
+                ChartsName::MEM_FREE => {
+                    let mem_free = askama::js_mem_free(ndata);
+                    js_options.push(labeled(mem_free));
+                },
+                ChartsName::MEM_ACTIVE => {
+                    let mem_active = askama::js_mem_active(ndata);
+                    js_options.push(labeled(mem_active));
+                },
+                ChartsName::MEM_SWAP => {
+                    let mem_swap = askama::js_mem_swap(ndata);
+                    js_options.push(labeled(mem_swap));
+                },
+                ChartsName::DISKBUSY_AWMN => {
+                    let diskbusy_awmn = askama::js_diskbusy_awmn(ndata);
+                    js_options.push(labeled(diskbusy_awmn));
+                },
+                ChartsName::DISKBUSY_OVERTIME => {
+                    let diskbusy_overtime = askama::js_diskbusy_overtime(ndata, options.disk_top);
+                    js_options.push(labeled(diskbusy_overtime));
+                },
+                ChartsName::DISK_SUMM => {
+                    let disk_summ = askama::js_disk_summ_overtime(ndata);
+                    js_options.push(labeled(disk_summ));
+                },
+                ChartsName::DISKREAD_AWMN => {
+                    let diskread_awmn = askama::js_disk_summ_awmn(ndata, name, "DISKREAD");
+                    js_options.push(labeled(diskread_awmn));
+                },
+                ChartsName::DISKWRITE_AWMN => {
+                    let diskwrite_awmn = askama::js_disk_summ_awmn(ndata, name, "DISKWRITE");
+                    js_options.push(labeled(diskwrite_awmn));
+                },
+                ChartsName::DISKXFER_AWMN => {
+                    let diskxfer_awmn = askama::js_disk_summ_awmn(ndata, name, "DISKXFER");
+                    js_options.push(labeled(diskxfer_awmn));
+                },
+                ChartsName::DISKBSIZE_AWMN => {
+                    let diskbsize_awmn = askama::js_disk_summ_awmn(ndata, name, "DISKBSIZE");
+                    js_options.push(labeled(diskbsize_awmn));
+                },
+                ChartsName::NET_TOTAL => {
+                    let net_total = askama::js_network_io_total(ndata);
+                    js_options.push(labeled(net_total));
+                },
+                ChartsName::NET_DEVICE => {
+                    let net_device = askama::js_network_io_device(ndata);
+                    js_options.push(labeled(net_device));
+                },
+                ChartsName::NET_PACKET => {
+                    let net_packet = askama::js_network_packet(ndata);
+                    js_options.push(labeled(net_packet));
+                },
+                ChartsName::TOP_CPU => {
+                    let top_cpu = askama::js_top_cpu(ndata, options.top_num);
+                    js_options.push(labeled(top_cpu));
+                },
+                ChartsName::TOP_RESDATA => {
+                    let top_res_data = askama::js_top_res_data(ndata, options.top_num);
+                    js_options.push(labeled(top_res_data));
+                },
+                ChartsName::VM_PAGING => {
+                    let vm_paging = askama::js_vm_paging(ndata);
+                    js_options.push(labeled(vm_paging));
+                },
+                ChartsName::PROC_RUNQUEUE => {
+                    let proc_runqueue = askama::js_proc_runqueue(ndata);
+                    js_options.push(labeled(proc_runqueue));
+                },
+                ChartsName::PROC_RATES => {
+                    let proc_rates = askama::js_proc_rates(ndata);
+                    js_options.push(labeled(proc_rates));
+                },
+                ChartsName::AIX_LPAR => {
+                    let aix_lpar = askama::js_aix_lpar(ndata);
+                    js_options.push(labeled(aix_lpar));
+                },
+                ChartsName::AIX_MEMNEW => {
+                    let aix_memnew = askama::js_aix_memnew(ndata);
+                    js_options.push(labeled(aix_memnew));
+                },
+                ChartsName::AIX_MEMUSE => {
+                    let aix_memuse = askama::js_aix_memuse(ndata);
+                    js_options.push(labeled(aix_memuse));
+                },
+                ChartsName::AIX_PAGE => {
+                    let aix_page = askama::js_aix_page(ndata);
+                    js_options.push(labeled(aix_page));
+                },
+                ChartsName::SPEC(spec) => {
+                    let chart = askama::js_chart_spec(ndata, spec);
+                    js_options.push(labeled(chart));
+                },
+                ChartsName::JTL => {
+                    let Some(ref jtl) = options.jtl else { continue };
+                    if ndata.measurement("CPU_ALL").is_some_and(|m| !jtl.overlaps(&m.zzzz())) {
+                        warn!("{}与{}的时间没有重叠，请检查--tz是否正确", jtl.path().display(), ndata.filename());
+                    }
+                    let jtl_chart = askama::js_jtl(ndata, jtl);
+                    js_options.push(labeled(jtl_chart));
+                },
+                // 对比图不属于单个nmon文件，在下面单独生成
+                ChartsName::CMP_CPU | ChartsName::CMP_MEM_FREE | ChartsName::CMP_DISKBUSY | ChartsName::CMP_NET => {},
+            }
+        }
+    }
+
+    // 多个nmon文件的对比图
+    for (name, _) in options.compares.iter() {
+        let chart_id = format!("compare_{}", name.id());
+        let option = match name {
+            ChartsName::CMP_CPU => askama::js_compare_cpu(nmon_datas),
+            ChartsName::CMP_MEM_FREE => askama::js_compare_mem_free(nmon_datas),
+            ChartsName::CMP_DISKBUSY => askama::js_compare_diskbusy(nmon_datas),
+            ChartsName::CMP_NET => askama::js_compare_net(nmon_datas),
+            _ => continue,
+        };
+        let option = EchartsOption::new(chart_id, option);
+        let option = match options.labels.get(&name.id()) {
+            Some(label) => option.with_label(label.title.clone(), label.unit.clone()),
+            None => option,
+        };
+        js_options.push(option);
+    }
+
+    let js_cache = JsCache::new(js_options).to_string();
+    let echarts = askama::js_echarts();
+    // --single-file时echarts库和图表数据内嵌到html文件里，watch时需要单独的图表数据文件以便重新加载
+    let single_file = options.single_file && options.refresh.is_none();
+    let inline = single_file.then(|| askama::inline_scripts(&echarts, &js_cache, options.compress_data));
+
+    // 保存html文件
+    // 选择列表里显示报告定义文件里指定的标题
+    let chart = |name: &(ChartsName, bool)| {
+        let chart: Chart = name.into();
+        match options.labels.get(&name.0.id()).and_then(|label| label.title.as_ref()) {
+            Some(title) => chart.with_label(title),
+            None => chart,
+        }
+    };
+    let html = askama::html(html_template_data,
+        options.charts.iter().map(chart).collect(),
+        options.compares.iter().map(chart).collect(),
+        options.refresh,
+        inline,
+        options.title.as_deref(),
+    );
+    write(path.join("index_nmons.html"), html)?;
+
+    if !single_file {
+        // 保存图表数据到js文件
+        write(path.join("index_nmons_data.js"), js_cache)?;
+        // 生成echarts.min.js
+        write(path.join("echarts.min.js"), echarts)?;
+    }
+
+    Ok(())
+}
+
+pub struct NmonData {
+    filename: String,
+    measurements: BTreeMap<String, Measurement>,
+    top: Option<TopProcesses>,
+    host: HostInfo,
+    diagnostics: Vec<NmonError>,
+}
+
+/// 解析nmon数据的选项
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// 只保留磁盘名称匹配的列，对所有DISK开头的指标生效
+    pub disk_filter: Option<Regex>,
+    /// 宽松模式，跳过无法解析的数据行或数据，而不是返回错误
+    pub lenient: bool,
+    /// ZZZZ时间序列所在的时区
+    pub tz: NmonTz,
+    /// 图表里每条曲线最多的点数，超过时用LTTB降采样，只影响图表，不影响统计值和导出的数据
+    pub max_points: Option<usize>,
+    /// 只保留该时间段内的数据，统计和图表都只基于该时间段
+    pub window: TimeWindow,
+}
+
+/// 严格模式下直接返回错误，--lenient时记录下来并跳过
+fn tolerate(err: NmonError, lenient: bool, diagnostics: &mut Vec<NmonError>) -> Result<(), NmonError> {
+    if lenient {
+        warn!("{}，--lenient跳过", err);
+        diagnostics.push(err);
+        Ok(())
+    } else {
+        Err(err)
+    }
+}
+
+impl NmonData {
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+    pub fn measurement(&self, name: &str) -> Option<&Measurement> {
+        self.measurements.get(name)
+    }
+    pub fn measurements(&self) -> &BTreeMap<String, Measurement> {
+        &self.measurements
+    }
+    /// nmon文件头AAA、BBBP里的服务器信息
+    pub fn host(&self) -> &HostInfo {
+        &self.host
+    }
+    /// 解析时跳过的数据，包括不完整的数据行以及--lenient时无法解析的数据
+    pub fn diagnostics(&self) -> &Vec<NmonError> {
+        &self.diagnostics
+    }
+    /// 只有收集的指标包含TOP时才有值
+    pub fn top(&self) -> Option<&TopProcesses> {
+        self.top.as_ref()
+    }
+    /// 内存使用率的时间序列及其所属的指标，
+    /// Linux: (memtotal - memfree - cached - buffers) / memtotal，AIX: MEMNEW的Process% + System%
+    pub fn mem_used(&self) -> Option<(&Measurement, Array1<f32>)> {
+        if let Some(mem) = self.measurement("MEM")
+            && let (Some(total), Some(free), Some(cached), Some(buffers)) =
+                (mem.column("memtotal"), mem.column("memfree"), mem.column("cached"), mem.column("buffers"))
+        {
+            let used = ndarray::Zip::from(&total).and(&free).and(&cached).and(&buffers)
+                .map_collect(|t, f, c, b| if *t == 0. { 0. } else { (t - f - c - b) / t * 100. });
+            return Some((mem, used));
+        }
+        let memnew = self.measurement("MEMNEW")?;
+        Some((memnew, memnew.column("Process%")? + memnew.column("System%")?))
+    }
+
+    /// 解析nmon文件
+    /// cell: 要收集的指标名称或glob，比如: CPU_ALL、MEM、CPU[0-9]*
+    pub fn new(path: &Path, cell: &HashSet<String>, options: &ParseOptions) -> Result<Self, NmonError> {
+        let filename = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_else(|| path.display().to_string());
+        let f = File::open(path).map_err(|e| NmonError::Io { path: path.to_path_buf(), source: e })?;
+        Self::from_reader(BufReader::new(f), &filename, cell, options)
+    }
+
+    /// 从reader解析nmon数据，比如内存里或解压后的nmon数据
+    /// filename: 错误信息和图表里显示的nmon文件名
+    /// cell: 要收集的指标名称或glob，比如: CPU_ALL、MEM、CPU[0-9]*
+    pub fn from_reader<R: BufRead>(br: R, filename: &str, cell: &HashSet<String>, options: &ParseOptions) -> Result<Self, NmonError> {
+        let mut parser = NmonParser::new(filename, cell, options);
+        for line in br.lines() {
+            match line {
+                Ok(line) => parser.push_line(&line)?,
+                Err(e) => parser.push_error(e)?,
+            }
+        }
+        parser.build()
+    }
+}
+
+/// 逐行解析nmon数据，保存各指标已解析的数据。watch时只解析新增的行，每次刷新报告时按已解析的数据生成NmonData
+pub(crate) struct NmonParser {
+    filename: String,
+    options: ParseOptions,
+    selected: SectionMatcher,
+    headers: BTreeMap<String, Vec<String>>, // csv headers
+    notes: BTreeMap<String, String>, // 各指标数据行的第二列
+    data: BTreeMap<String, Vec<f32>>, // 各指标数据行的[2..]
+    lens: BTreeMap<String, usize>, // 各指标数据行的行数
+    zzzz: Vec<DateTime<FixedOffset>>, // 图表X轴的时间序列, ZZZZ,T0120,17:42:21,16-JUN-2020
+    snapshots: HashMap<String, usize>, // ZZZZ时间序列标识T0120及其在zzzz中的下标，TOP数据行使用
+    top: Option<TopBuilder>,
+    host: HostInfo, // 文件头AAA、BBBP里的服务器信息
+    diagnostics: Vec<NmonError>, // 跳过的数据
+    skip_snapshot: bool, // ZZZZ无法使用时，跳过该快照的数据行，直到下一个ZZZZ
+    lineno: usize, // 已解析的行数
+}
+
+impl NmonParser {
+    /// filename: 错误信息和图表里显示的nmon文件名
+    /// cell: 要收集的指标名称或glob，比如: CPU_ALL、MEM、CPU[0-9]*
+    pub(crate) fn new(filename: &str, cell: &HashSet<String>, options: &ParseOptions) -> Self {
+        debug!("打算收集的指标：{:?}", cell);
+        let mut selected = SectionMatcher::new(cell);
+        let top = if selected.is_selected("TOP") { Some(TopBuilder::new(filename)) } else { None };
+        Self {
+            filename: filename.to_string(),
+            options: options.clone(),
+            selected,
+            headers: BTreeMap::new(),
+            notes: BTreeMap::new(),
+            data: BTreeMap::new(),
+            lens: BTreeMap::new(),
+            zzzz: Vec::new(),
+            snapshots: HashMap::new(),
+            top,
+            host: HostInfo::default(),
+            diagnostics: Vec::new(),
+            skip_snapshot: false,
+            lineno: 0,
+        }
+    }
+
+    /// 读取某一行失败，比如不是UTF-8
+    pub(crate) fn push_error(&mut self, e: std::io::Error) -> Result<(), NmonError> {
+        self.lineno += 1;
+        tolerate(NmonError::Line { file: self.filename.clone(), line: self.lineno, source: e }, self.options.lenient, &mut self.diagnostics)
+    }
+
+    /// 解析一行完整的nmon数据，严格模式下出错的行不会加入已解析的数据
+    pub(crate) fn push_line(&mut self, line: &str) -> Result<(), NmonError> {
+        self.lineno += 1;
+        let lineno = self.lineno;
+        let Self { filename, options, selected, headers, notes, data, lens, zzzz, snapshots, top, host, diagnostics, skip_snapshot, .. } = self;
+        let (lenient, tz) = (options.lenient, &options.tz);
+        let vec: Vec<&str> = line.split(',').collect();
+        let name = vec[0];
+        trace!("[{}]文件, 处理数据行: {}", filename, line);
+        trace!("[{}]文件, 处理数据行转换为数组，其长度为：{}，数组为：{:?}", filename, vec.len(), vec);
+        // 判定是否为时间序列
+        // ZZZZ,T0001,17:40:19,16-JUN-2020
+        if name == "ZZZZ" {
+            if vec.len() < 4 {
+                tolerate(NmonError::Incomplete { file: filename.clone(), line: lineno, section: name.to_string(), expected: 2, actual: vec.len().saturating_sub(2) }, lenient, diagnostics)?;
+                *skip_snapshot = true;
+                return Ok(());
+            }
+            let time_str = vec[2];
+            let date_str = vec[3];
+            let s = format!("{}T{}", date_str, time_str);
+            let fmt = "%d-%b-%YT%H:%M:%S";
+            let ndt = match NaiveDateTime::parse_from_str(&s, fmt) {
+                Ok(ndt) => tz.localize(&ndt),
+                Err(e) => {
+                    tolerate(NmonError::Timestamp { file: filename.clone(), line: lineno, value: line.to_string(), source: e }, lenient, diagnostics)?;
+                    // 沿用上一个时间戳，保证后面的指标数据与时间序列对齐
+                    match zzzz.last() {
+                        Some(last) => *last,
+                        None => {
+                            *skip_snapshot = true;
+                            return Ok(());
+                        },
+                    }
+                }
+            };
+            *skip_snapshot = false;
+            snapshots.insert(vec[1].to_string(), zzzz.len());
+            zzzz.push(ndt);
+            trace!("[{}]文件, 收集时间戳到ZZZZ数据: {:?}", filename, ndt);
+        }
+        // 文件头里的服务器信息
+        if name == "AAA" {
+            host.push_aaa(&vec);
+            return Ok(());
+        }
+        if name == "BBBP" {
+            host.push_bbbp(&vec);
+            return Ok(());
+        }
+        // 跳过的快照里的数据行，列名行在第一个ZZZZ之前，不受影响
+        if *skip_snapshot && headers.contains_key(name) {
+            trace!("[{}]文件, ZZZZ无法使用，跳过该快照的数据行: {}", filename, line);
+            return Ok(());
+        }
+        // TOP、UARG数据行的格式与其他指标不同，单独处理
+        if name == "TOP" || name == "UARG" {
+            if let Some(top) = top {
+                if name == "TOP" {
+                    // --lenient时跳过无法解析的进程行
+                    if let Err(err) = top.push_top(&vec, line, lineno, snapshots) {
+                        tolerate(err, lenient, diagnostics)?;
+                    }
+                } else {
+                    top.push_uarg(&vec, line);
+                }
+            }
+            return Ok(());
+        }
+        // 判定是否为要收集的指标
+        if vec.len() >= 2 && selected.is_selected(name) {
+            trace!("[{}]文件, 开始处理要收集要的指标数据", filename);
+            if !headers.contains_key(name) {
+                let mut header: Vec<String> = vec[2..].iter().map(|e| e.to_string()).collect();
+                let mut note = vec[1].to_string();
+                trace!("[{}]文件, [{}]指标数据header头转换为数组: {:?}", filename, name, header);
+                // VM	"Paging and Virtual Memory"	nr_dirty	nr_writeback	nr_unstable	nr_page_table_pages	nr_mapped	nr_slab_reclaimable	pgpgin	pgpgout	pswpin	pswpout	pgfree	pgactivate	pgdeactivate	pgfault	pgmajfault	pginodesteal	slabs_scanned	kswapd_steal	kswapd_inodesteal	pageoutrun	allocstall	pgrotated	pgalloc_high	pgalloc_normal	pgalloc_dma	pgrefill_high	pgrefill_normal	pgrefill_dma	pgsteal_high	pgsteal_normal	pgsteal_dma	pgscan_kswapd_high	pgscan_kswapd_normal	pgscan_kswapd_dma	pgscan_direct_high	pgscan_direct_normal	pgscan_direct_dma
+                // VM	T0001	27	0	0	6688	59502	23700	1040	80	0	0	8848	84	0	12898	8	0	0	0	0	0	0	0	0	8711	0	0	0	0	0	0	0	0	0	0	0	0	0
+                // ...
+                // VM	T0001	"Paging and Virtual Memory"	nr_dirty	nr_writeback	nr_unstable	nr_page_table_pages	nr_mapped	nr_slab	pgpgin	pgpgout	pswpin	pswpout	pgfree	pgactivate	pgdeactivate	pgfault	pgmajfault	pginodesteal	slabs_scanned	kswapd_steal	kswapd_inodesteal	pageoutrun	allocstall	pgrotated	pgalloc_high	pgalloc_normal	pgalloc_dma	pgrefill_high	pgrefill_normal	pgrefill_dma	pgsteal_high	pgsteal_normal	pgsteal_dma	pgscan_kswapd_high	pgscan_kswapd_normal	pgscan_kswapd_dma	pgscan_direct_high	pgscan_direct_normal	pgscan_direct_dma
+                // VM	T0001	-1	-1	-1	8077	-1	-1	-1	-1	-1	-1	-1	-1	-1	-1	-1	5800	4169	-1	-1	0	32	-1	7475286	-1	-1	-1	-1	-1	-1	0	544100	-1	81	820	0	-1	-1
+                if name == "VM" {
+                    // 当指标列名note以"T"开头时，则列名长度比数据列长度多1，故列名数据减1
+                    if note.starts_with("T") {
+                        header = vec[3..].iter().map(|e| e.to_string()).collect();
+                        note = vec[2].to_string();
+                        trace!("[{}]文件, [VM]指标数据header头特殊处理，转换为数组: {:?}", filename, header);
+                    }
+                }
+                headers.insert(name.to_string(), header);
+                notes.insert(name.to_string(), note);
+                data.insert(name.to_string(), Vec::new());
+                lens.insert(name.to_string(), 0);
+            } else {
+                // 数据列名的长度是否相等
+                let header = headers.get(name).unwrap();
+                let name_len = header.len();
+                trace!("[{}]文件, [{}]指标数据，列名长度：{}", filename, name, name_len);
+                // 增加数据
+                let value = data.get_mut(name).unwrap();
+                trace!("[{}]文件, [{}]指标数据，已增加到data数组里的长度为：{}", filename, name, value.len());
+                // 情况1：
+                // net 的数据列有可能在运行时多出几列，按列名长度，忽略多出的列
+                // 处理方式： vec[2..name_len + 2], 直接截断多余的数据
+                //
+                // 情况2:
+                // range out index 60 out of range for slice of length 53
+                // 当测试突然停止，从服务器取得nmon文件时，可能服务器正在向nmon文件中向数据，此时取nmon文件可能导致指标数据没写完，导致数据操作失败
+                // 处理方式：数据长度大于等于列名长度才收集该数据，否则抛弃该数据
+                let data_len = vec[2..].len();
+                if data_len < name_len {
+                    let err = NmonError::Incomplete { file: filename.clone(), line: lineno, section: name.to_string(), expected: name_len, actual: data_len };
+                    warn!("{}，抛弃它：{}", err, line);
+                    diagnostics.push(err);
+                }else {
+                    // 整行解析完才加入，出错时不会只加入半行
+                    let mut row = Vec::with_capacity(name_len);
+                    for (column, e) in header.iter().zip(vec[2..name_len + 2].iter()) {
+                        if e.is_empty() {
+                            row.push(0.0);
+                        } else {
+                            match f32::from_str(e) {
+                                Ok(e) => row.push(e),
+                                Err(_) => {
+                                    // --lenient时记为NaN，统计时忽略，图表里为断点
+                                    tolerate(NmonError::Value { file: filename.clone(), line: lineno, section: name.to_string(), column: column.clone(), value: e.to_string() }, lenient, diagnostics)?;
+                                    row.push(f32::NAN);
+                                }
+                            }
+                        }
+                    }
+                    value.extend(row);
+
+                    // 指标长度加一，方便下面检查数据
+                    let l = lens.get_mut(name).unwrap();
+                    *l += 1;
+                    trace!("[{}]文件, [{}]指标数据，已收集的数据长度加1后值为：{}", filename, name, l);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 按已解析的数据生成NmonData，之后仍可继续解析新增的行。跳过的数据只在日志里，不复制到NmonData里
+    pub(crate) fn snapshot(&self) -> Result<NmonData, NmonError> {
+        Self {
+            filename: self.filename.clone(),
+            options: self.options.clone(),
+            selected: self.selected.clone(),
+            headers: self.headers.clone(),
+            notes: self.notes.clone(),
+            data: self.data.clone(),
+            lens: self.lens.clone(),
+            zzzz: self.zzzz.clone(),
+            snapshots: HashMap::new(),
+            top: self.top.clone(),
+            host: self.host.clone(),
+            diagnostics: Vec::new(),
+            skip_snapshot: false,
+            lineno: self.lineno,
+        }
+        .build()
+    }
+
+    /// 按已解析的数据生成NmonData
+    pub(crate) fn build(self) -> Result<NmonData, NmonError> {
+        let NmonParser { filename, options, selected, mut headers, mut notes, mut data, mut lens, mut zzzz, top, host, diagnostics, .. } = self;
+        let (tz, window, disk_filter) = (&options.tz, &options.window, options.disk_filter.as_ref());
+
+        // 只有列名行的指标，以及glob匹配的、只在部分快照里有数据的指标，不参与下面的截取，行数不足时不收集
+        let named_min = lens
+            .iter()
+            .filter(|(name, l)| selected.is_named(name) && **l > 0)
+            .map(|(_, l)| *l)
+            .chain(std::iter::once(zzzz.len()))
+            .min()
+            .unwrap_or_default();
+        let partial: Vec<String> = lens
+            .iter()
+            .filter(|(name, l)| **l < named_min && (**l == 0 || !selected.is_named(name)))
+            .map(|(name, _)| name.clone())
+            .collect();
+        for name in partial.iter() {
+            warn!("[{}]文件，[{}]指标数据行数{} < {}，不收集该指标", filename, name, lens[name], named_min);
+            headers.remove(name);
+            notes.remove(name);
+            data.remove(name);
+            lens.remove(name);
+        }
+
+        // 各指标数据长度以及ZZZZ长度有可能不一致，取最小化长度，忽略多出的数据
+        let mut vec_len: Vec<usize> = lens.values().copied().collect();
+        vec_len.push(zzzz.len());
+        let min = *vec_len.iter().min().unwrap();
+        if min < zzzz.len() {
+            warn!(
+                "[{}]文件，数据解析ZZZZ时间序列长度{} > 指标数据最小行数{}, 以最小行数截取ZZZZ时间序列数据",
+                filename,
+                zzzz.len(),
+                min
+            );
+            zzzz.truncate(min);
+        }
+        for (key, vec) in data.iter_mut() {
+            let colsize = headers.get(key).unwrap().len();
+            let rowsize = *lens.get(key).unwrap();
+            if min < rowsize {
+                warn!(
+                    "[{}]文件，[{}]指标数据行数{} > 指标数据行数最小值orZZZZ时间序列数据行数{}, 以最小行数截取指标数据",
+                    filename, key, rowsize, min
+                );
+                vec.truncate(min * colsize);
+            }
+        }
+
+        // 按--from/--to截取时间段内的ZZZZ时间序列和指标数据
+        let range = window.range(&zzzz, tz);
+        let mut top = top.map(|top| top.build(&zzzz, options.max_points));
+        if !window.is_all() {
+            if range.is_empty() {
+                let fmt = |x: Option<&DateTime<FixedOffset>>| x.map(|x| format!("{}", x.format(POINT_X_FORMAT))).unwrap_or_default();
+                return Err(NmonError::EmptyWindow { file: filename, first: fmt(zzzz.first()), last: fmt(zzzz.last()) });
+            }
+            debug!("[{}]文件，按--from/--to截取ZZZZ时间序列的第{}到{}个数据", filename, range.start + 1, range.end);
+            for (key, vec) in data.iter_mut() {
+                let colsize = headers.get(key).unwrap().len();
+                *vec = vec[range.start * colsize..range.end * colsize].to_vec();
+            }
+            if let Some(ref mut top) = top {
+                top.retain_rows(range.clone());
+            }
+            zzzz = zzzz[range.clone()].to_vec();
+        }
+
+        // 数据转换为ndarray里的Array2结构
+        // let mut ndata: BTreeMap<String, _> = BTreeMap::new();
+        // let mut ndata: BTreeMap<String, ndarray::ArrayBase<ndarray::OwnedRepr<f32>, ndarray::Ix2>> = BTreeMap::new();
+        let rowsize = zzzz.len();
+        // let mut ndata = BTreeMap::new();
+        let mut measurements = BTreeMap::new();
+        for (name, vec) in data.into_iter() {
+            let header = headers.get(&name).unwrap();
+            let colsize = header.len();
+            let note = notes.get(&name).unwrap();
+            let mut measurement = Measurement::new(
+                &filename,
+                &name,
+                note,
+                header.clone(),
+                &zzzz,
+                vec,
+                (rowsize, colsize),
+            )?;
+            measurement.set_max_points(options.max_points);
+            measurements.insert(name.to_string(), measurement);
+        }
+
+        // 按磁盘名称过滤DISK开头的指标
+        if let Some(re) = disk_filter {
+            for (name, measurement) in measurements.iter_mut().filter(|(name, _)| name.starts_with("DISK")) {
+                measurement.retain_columns(|header| re.is_match(header));
+                debug!("[{}]文件, [{}]指标按--disk-filter过滤后的磁盘: {:?}", filename, name, measurement.header());
+            }
+        }
+
+        Ok(NmonData {
+            filename,
+            measurements,
+            top,
+            host,
+            diagnostics,
+        })
+    }
+}
+
+pub struct Measurement {
+    filename: String,
+    name: String,
+    note: String,
+    header: Vec<String>,
+    zzzz: Vec<DateTime<FixedOffset>>,
+    // zzzz: Array1<DateTime<FixedOffset>>,
+    data: Array2<f32>,
+    // 图表里每条曲线最多的点数，超过时降采样
+    max_points: Option<usize>,
+    // 降采样保留的行下标，各列共用，同一图表里的曲线时间点一致
+    sample: Option<Vec<usize>>,
+}
+
+impl Measurement {
+    pub fn new(
+        filename: &str,
+        name: &str,
+        note: &str,
+        header: Vec<String>,
+        zzzz: &[DateTime<FixedOffset>],
+        // zzzz: Array1<DateTime<FixedOffset>>,
+        data: Vec<f32>,
+        (rowsize, colsize): (usize, usize),
+    ) -> Result<Self, NmonError> {
+        let array2 =
+            Array2::from_shape_vec((rowsize, colsize).set_f(false), data).map_err(|_| NmonError::Shape {
+                file: filename.to_string(),
+                section: name.to_string(),
+                rows: rowsize,
+                cols: colsize,
+            })?;
+        Ok(Self {
+            filename: filename.to_string(),
+            name: name.to_string(),
+            note: note.to_string(),
+            header,
+            zzzz: zzzz.to_vec(),
+            data: array2,
+            max_points: None,
+            sample: None,
+        })
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+    pub fn note(&self) -> &str {
+        &self.note
+    }
+    pub fn header(&self) -> &Vec<String> {
+        &self.header
+    }
+    pub fn zzzz(&self) -> Vec<DateTime<FixedOffset>> {
+        self.zzzz.clone()
+    }
+    /// 按ZZZZ时间序列生成图表的点，点数超过--max-points时用LTTB降采样
+    pub(crate) fn points(&self, values: &[f32]) -> Vec<Point> {
+        let len = self.zzzz.len().min(values.len());
+        let point = |i: usize| Point::new(format!("{}", self.zzzz[i].format(POINT_X_FORMAT)), values[i]);
+        match self.sample {
+            Some(ref sample) => sample.iter().copied().filter(|i| *i < len).map(point).collect(),
+            None => (0..len).map(point).collect(),
+        }
+    }
+    /// 同一图表里不是本指标的列的曲线(比如多个指标的合计)，按这些曲线共用的下标降采样
+    pub(crate) fn points_shared(&self, series: &[Vec<f32>]) -> Vec<Vec<Point>> {
+        let len = series.iter().map(|ys| ys.len()).min().unwrap_or_default().min(self.zzzz.len());
+        let idxs = match self.max_points {
+            Some(max) if len > max => downsample::lttb_shared(series, max),
+            _ => (0..len).collect(),
+        };
+        series
+            .iter()
+            .map(|ys| {
+                idxs.iter()
+                    .map(|i| Point::new(format!("{}", self.zzzz[*i].format(POINT_X_FORMAT)), ys[*i]))
+                    .collect()
+            })
+            .collect()
+    }
+    /// 设置图表里每条曲线最多的点数，各列按共用的LTTB下标降采样
+    pub fn set_max_points(&mut self, max_points: Option<usize>) {
+        self.max_points = max_points;
+        self.sample = match max_points {
+            Some(max) if self.zzzz.len() > max => {
+                let series: Vec<Vec<f32>> = self.data.columns().into_iter().map(|c| c.to_vec()).collect();
+                Some(downsample::lttb_shared(&series, max))
+            },
+            _ => None,
+        };
+    }
+    /// 数据矩阵，每行为一个时间序列，每列与header对应
+    pub fn data(&self) -> &Array2<f32> {
+        &self.data
+    }
+    pub(crate) fn column_echartjs_vec(&self, columns: &[&str]) -> Vec<(String, Vec<Point>)> {
+        let mut vec = Vec::new();
+        for name in columns {
+            if let Some(echarsjs_data) = self.column_echartjs_overtime(name) {
+                vec.push((name.to_string(), echarsjs_data));
+            }
+        }
+        vec
+    }
+    // pub fn columns_echarsjs_awmm(&self) -> Vec<(String, Vec<Point>)> {
+    //     let mut vec = Vec::new();
+    //     for name in self.columns_echartjs_overtime() {
+    //         if let Some(echarsjs_data) = self.column_echartjs_overtime(&name) {
+    //             vec.push((name, echarsjs_data));
+    //         }
+    //     }
+    //     vec
+    // }
+
+    // pub fn xalis_datetime_range(&self) -> Range<DateTime<FixedOffset>> {
+    //     let len = self.zzzz.len();
+    //     // 17:40:19,16-JUN-2020
+    //     // let start = self.zzzz[0].clone();
+    //     let start = self.zzzz[0];
+    //     //17:42:21,16-JUN-2020
+    //     // let xend = DateTime::parse_from_str("16-JUN-2020T17:42:28 +0000", "%d-%b-%YT%H:%M:%S %z").unwrap();
+    //     let end = self.zzzz[len - 1] + Duration::from_secs(1);
+    //     start..end
+    // }
+
+    /// 只保留列名满足条件的列
+    pub fn retain_columns<F>(&mut self, f: F)
+    where
+        F: Fn(&str) -> bool,
+    {
+        let idxs: Vec<usize> = self
+            .header
+            .iter()
+            .enumerate()
+            .filter(|(_idx, e)| f(e))
+            .map(|(idx, _)| idx)
+            .collect();
+        self.header = idxs.iter().map(|idx| self.header[*idx].clone()).collect();
+        self.data = self.data.select(Axis(1), &idxs);
+        self.set_max_points(self.max_points);
+    }
+
+    fn idx_of_column(&self, name: &str) -> Option<usize> {
+        self.header
+            .iter()
+            .enumerate()
+            .find(|(_idx, e)| **e == name)
+            .map(|(idx, _)| idx)
+    }
+
+    pub fn column(&self, name: &str) -> Option<Array1<f32>> {
+        if let Some(idx) = self.idx_of_column(name) {
+            let array1 = self.data.column(idx).map(|x| x.to_owned());
+            Some(array1)
+        } else {
+            None
+        }
+    }
+    /// 某列所有数据求均值，没有数据时为NaN
+    pub fn column_mean(&self, name: &str) -> Option<f32> {
+        self.column(name).map(|array1| stats::mean(&array1.to_vec()))
+    }
+    pub(crate) fn column_echartjs_overtime(&self, name: &str) -> Option<Vec<Point>> {
+        if let Some(idx) = self.idx_of_column(name) {
+            let array1 = self.data.column(idx);
+            Some(self.points(&array1.to_vec()))
+        } else {
+            None
+        }
+    }
+
+    /// 各列分别取统计值，没有数据时为NaN
+    fn rows_stat(&self, stat: fn(&[f32]) -> f32) -> Vec<f32> {
+        self.data
+            .columns()
+            .into_iter()
+            .map(|col| stat(&col.to_vec()))
+            .collect()
+    }
+    pub fn rows_max(&self) -> Vec<f32> {
+        self.rows_stat(stats::max)
+    }
+    pub fn rows_min(&self) -> Vec<f32> {
+        self.rows_stat(stats::min)
+    }
+    /// 按时间序列把每行的数据后，再取平均
+    pub fn rows_mean(&self) -> Vec<f32> {
+        self.rows_stat(stats::mean)
+    }
+    /// 按时间序列，求取标准：
+    pub fn rows_stdev(&self) -> Vec<f32> {
+        self.rows_stat(stats::stdev)
+    }
+    /// 按时间序列，求取wavg：
+    pub fn rows_wavg(&self) -> Vec<f32> {
+        self.rows_stat(stats::wavg)
+    }
+    /// 按时间序列，求取百分位数，q取值0~1，比如0.9为p90，线性插值
+    pub fn rows_quantile(&self, q: f64) -> Vec<f32> {
+        let mut data = self.data.clone();
+        data.quantile_axis_skipnan_mut(Axis(0), n64(q), &Linear)
+            .map(|array1| array1.to_vec())
+            .unwrap_or_else(|_| vec![f32::NAN; self.header.len()])
+    }
+    // pub fn rows_sum_iter(&self) -> impl Iterator<Item = f32> {
+    //     self.data.rows().into_iter().map(|column| column.sum())
+    // }
+
+    pub(crate) fn columns_echartjs_overtime(&self) -> Vec<(String, Vec<Point>)> {
+        self.header
+            .iter()
+            .zip(self.data.columns())
+            .map(|(header, column)| (header.clone(), self.points(&column.to_vec())))
+            .collect()
+    }
+    /// 按时间序列，每个时间点所有列求和，比如DISKREAD各磁盘读KB/s的合计
+    pub fn sum_overtime(&self) -> Array1<f32> {
+        self.data
+            .rows()
+            .into_iter()
+            .map(|column| column.sum())
+            .collect()
+    }
+    /// 按时间序列，每个时间点所有列取最大值，比如DISKBUSY最繁忙的磁盘
+    pub fn max_overtime(&self) -> Array1<f32> {
+        self.data
+            .rows()
+            .into_iter()
+            .map(|row| row.iter().cloned().fold(0., f32::max))
+            .collect()
+    }
+    /// 按时间序列，只对指定的列求和，比如NET里所有网卡的read-KB/s
+    pub(crate) fn columns_sum_echartjs_overtime(&self, columns: &[&str]) -> Vec<Point> {
+        let idxs: Vec<usize> = columns
+            .iter()
+            .filter_map(|name| self.idx_of_column(name))
+            .collect();
+        let array1: Array1<f32> = self
+            .data
+            .rows()
+            .into_iter()
+            .map(|row| idxs.iter().map(|idx| row[*idx]).sum())
+            .collect();
+        self.points(&array1.to_vec())
+    }
+    /// 列名以suffix结尾的所有列，比如NET里的"-read-KB/s"
+    pub fn header_ends_with(&self, suffix: &str) -> Vec<&str> {
+        self.header
+            .iter()
+            .filter(|h| h.ends_with(suffix))
+            .map(|h| h.as_str())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn parse(nmon: &str, lenient: bool) -> Result<NmonData, NmonError> {
+        let cell: HashSet<String> = ["CPU_ALL".to_string()].into_iter().collect();
+        let options = ParseOptions { lenient, ..Default::default() };
+        NmonData::from_reader(Cursor::new(nmon.to_string()), "app01.nmon", &cell, &options)
+    }
+
+    #[test]
+    fn lenient_bad_value_is_nan() {
+        let nmon = "CPU_ALL,CPU Total app01,User%,Idle%\n\
+                    ZZZZ,T0001,17:40:01,16-JUN-2020\n\
+                    CPU_ALL,T0001,10.0,90.0\n\
+                    ZZZZ,T0002,17:40:02,16-JUN-2020\n\
+                    CPU_ALL,T0002,abc,80.0\n\
+                    ZZZZ,T0003,17:40:03,16-JUN-2020\n\
+                    CPU_ALL,T0003,30.0,70.0\n";
+        assert!(parse(nmon, false).is_err());
+        let ndata = parse(nmon, true).unwrap();
+        let cpu_all = ndata.measurement("CPU_ALL").unwrap();
+        assert!(cpu_all.column("User%").unwrap()[1].is_nan());
+        assert_eq!(cpu_all.rows_mean()[0], 20.);
+        assert_eq!(cpu_all.rows_min()[0], 10.);
+        assert_eq!(ndata.diagnostics().len(), 1);
+    }
+
+    #[test]
+    fn lenient_bad_first_zzzz_skips_its_rows() {
+        let nmon = "CPU_ALL,CPU Total app01,User%,Idle%\n\
+                    ZZZZ,T0001,bad,16-JUN-2020\n\
+                    CPU_ALL,T0001,99.0,1.0\n\
+                    ZZZZ,T0002,17:40:02,16-JUN-2020\n\
+                    CPU_ALL,T0002,20.0,80.0\n\
+                    ZZZZ,T0003,17:40:03,16-JUN-2020\n\
+                    CPU_ALL,T0003,30.0,70.0\n";
+        let ndata = parse(nmon, true).unwrap();
+        let cpu_all = ndata.measurement("CPU_ALL").unwrap();
+        assert_eq!(cpu_all.zzzz().len(), 2);
+        assert_eq!(cpu_all.column("User%").unwrap().to_vec(), vec![20., 30.]);
+        assert_eq!(format!("{}", cpu_all.zzzz()[0].format("%H:%M:%S")), "17:40:02");
+    }
+
+    #[test]
+    fn window_slices_zzzz_and_rows() {
+        let nmon = "CPU_ALL,CPU Total app01,User%,Idle%\n\
+                    MEM,Memory MB app01,memtotal,memfree\n\
+                    TOP,+PID,Time,%CPU,%Usr,%Sys,Size,ResSet,ResText,ResData,ShdLib,MinorFault,MajorFault,Command\n\
+                    ZZZZ,T0001,17:40:00,16-JUN-2020\n\
+                    CPU_ALL,T0001,10.0,90.0\n\
+                    MEM,T0001,1000.0,100.0\n\
+                    TOP,0001234,T0001,10.0,9.0,1.0,1,1,1,100,1,1,0,java\n\
+                    ZZZZ,T0002,17:40:10,16-JUN-2020\n\
+                    CPU_ALL,T0002,20.0,80.0\n\
+                    MEM,T0002,1000.0,200.0\n\
+                    TOP,0001234,T0002,20.0,18.0,2.0,1,1,1,200,1,1,0,java\n\
+                    ZZZZ,T0003,17:40:20,16-JUN-2020\n\
+                    CPU_ALL,T0003,30.0,70.0\n\
+                    MEM,T0003,1000.0,300.0\n\
+                    TOP,0001234,T0003,30.0,27.0,3.0,1,1,1,300,1,1,0,java\n\
+                    ZZZZ,T0004,17:40:30,16-JUN-2020\n\
+                    CPU_ALL,T0004,40.0,60.0\n\
+                    MEM,T0004,1000.0,400.0\n\
+                    TOP,0001234,T0004,40.0,36.0,4.0,1,1,1,400,1,1,0,java\n";
+        let cell: HashSet<String> = ["CPU_ALL", "MEM", "TOP"].iter().map(|s| s.to_string()).collect();
+        let window = TimeWindow::new(Some("+10s".parse().unwrap()), Some("17:40:20".parse().unwrap()));
+        let options = ParseOptions { tz: "+08:00".parse().unwrap(), window, ..Default::default() };
+        let ndata = NmonData::from_reader(Cursor::new(nmon.to_string()), "app01.nmon", &cell, &options).unwrap();
+        for name in ["CPU_ALL", "MEM"] {
+            let measurement = ndata.measurement(name).unwrap();
+            let zzzz: Vec<String> = measurement.zzzz().iter().map(|x| format!("{}", x.format("%H:%M:%S"))).collect();
+            assert_eq!(zzzz, vec!["17:40:10", "17:40:20"]);
+        }
+        assert_eq!(ndata.measurement("CPU_ALL").unwrap().column("User%").unwrap().to_vec(), vec![20., 30.]);
+        assert_eq!(ndata.measurement("MEM").unwrap().column("memfree").unwrap().to_vec(), vec![200., 300.]);
+        let top = ndata.top().unwrap();
+        assert_eq!(top.cpu()["java"], vec![20., 30.]);
+        assert_eq!(top.res_data()["java"], vec![200., 300.]);
+
+        let window = TimeWindow::new(Some("+1h".parse().unwrap()), None);
+        let options = ParseOptions { window, ..Default::default() };
+        let err = NmonData::from_reader(Cursor::new(nmon.to_string()), "app01.nmon", &cell, &options);
+        assert!(matches!(err, Err(NmonError::EmptyWindow { .. })));
+    }
+
+    #[test]
+    fn parser_continues_after_snapshot() {
+        let cell: HashSet<String> = ["CPU_ALL", "MEM"].iter().map(|s| s.to_string()).collect();
+        let mut parser = NmonParser::new("app01.nmon", &cell, &ParseOptions::default());
+        for line in [
+            "CPU_ALL,CPU Total app01,User%,Idle%",
+            "MEM,Memory MB app01,memtotal,memfree",
+            "ZZZZ,T0001,17:40:00,16-JUN-2020",
+            "CPU_ALL,T0001,10.0,90.0",
+            "MEM,T0001,1000.0,100.0",
+            "ZZZZ,T0002,17:40:10,16-JUN-2020",
+            "CPU_ALL,T0002,20.0,80.0",
+        ] {
+            parser.push_line(line).unwrap();
+        }
+        // 第二个快照的MEM还没写入，只有一个完整的快照
+        let ndata = parser.snapshot().unwrap();
+        assert_eq!(ndata.measurement("CPU_ALL").unwrap().column("User%").unwrap().to_vec(), vec![10.]);
+        assert_eq!(ndata.measurement("MEM").unwrap().column("memfree").unwrap().to_vec(), vec![100.]);
+
+        // 严格模式下出错的行不加入已解析的数据
+        assert!(parser.push_line("MEM,T0002,1000.0,abc").is_err());
+        parser.push_line("MEM,T0002,1000.0,200.0").unwrap();
+        let ndata = parser.build().unwrap();
+        assert_eq!(ndata.measurement("CPU_ALL").unwrap().column("User%").unwrap().to_vec(), vec![10., 20.]);
+        assert_eq!(ndata.measurement("MEM").unwrap().column("memfree").unwrap().to_vec(), vec![100., 200.]);
+    }
+}
@@ -1,5 +1,11 @@
 
 use crate::NmonData;
+use crate::baseline::DiffRow;
+use crate::error::NmonError;
+use crate::rule::RuleResult;
+use crate::section::FileSections;
+use crate::stats;
+use crate::top::TopProcesses;
 
 
 use std::fmt::Display;
@@ -23,23 +29,172 @@ pub fn save(path: PathBuf , data: String ) {
 
 
 
-pub fn console_print_cpuall(nmon: &NmonData) -> String {
-    let cpu_all = nmon.measurement("CPU_ALL").unwrap();
-    let filename = cpu_all.filename();
-    let mean = cpu_all.rows_mean();
-    let stdev = cpu_all.rows_stdev();
+pub fn console_print_host(nmon: &NmonData) -> String {
+    let mut res_nmon_text = String::new();
+    let mut builder = tabled::builder::Builder::default();
+    builder.push_record(["FILENAME", nmon.filename()]);
+    for (key, value) in nmon.host().summary() {
+        builder.push_record([key, value]);
+    }
+    let table = builder.build().with(Style::rounded()).to_string();
+    println!("{}", table);
+    res_nmon_text.push_str(table.as_str());
+
+    res_nmon_text
+}
+
+/// 各指标各列的统计值，每行为一列数据
+fn summary_builder(nmon: &NmonData) -> tabled::builder::Builder {
+    let mut builder = tabled::builder::Builder::default();
+    builder.push_record(["FILENAME", "KEY", "COLUMN", "min", "max", "mean", "wavg", "stdev", "p50", "p90", "p95", "p99"]);
+    for (name, measurement) in nmon.measurements().iter() {
+        let stats = [
+            measurement.rows_min(),
+            measurement.rows_max(),
+            measurement.rows_mean(),
+            measurement.rows_wavg(),
+            measurement.rows_stdev(),
+            measurement.rows_quantile(0.5),
+            measurement.rows_quantile(0.9),
+            measurement.rows_quantile(0.95),
+            measurement.rows_quantile(0.99),
+        ];
+        for (idx, column) in measurement.header().iter().enumerate() {
+            let mut record = vec![nmon.filename().to_string(), name.clone(), column.clone()];
+            record.extend(stats.iter().map(|stat| format!("{:.2}", stat[idx])));
+            builder.push_record(record);
+        }
+    }
+    builder
+}
 
+/// 打印各指标各列的统计值: min, max, mean, wavg, stdev, p50, p90, p95, p99
+pub fn console_print_summary(nmon: &NmonData) -> String {
     let mut res_nmon_text = String::new();
-    let measurement_name = cpu_all.name();
-    let header = cpu_all.header();
+    let table = summary_builder(nmon).build().with(Style::rounded()).to_string();
+    println!("{}", table);
+    res_nmon_text.push_str(table.as_str());
+
+    res_nmon_text
+}
+
+/// Markdown格式的各nmon文件各指标的统计值，每个nmon文件一个表格
+pub fn markdown_summary(nmon_datas: &[NmonData]) -> String {
+    let mut res_nmon_md = String::new();
+    for nmon in nmon_datas.iter() {
+        res_nmon_md.push_str(&format!("### {}\n\n", nmon.filename()));
+        res_nmon_md.push_str(&summary_builder(nmon).build().with(Style::markdown()).to_string());
+        res_nmon_md.push_str("\n\n");
+    }
+    res_nmon_md
+}
+
+pub fn console_print_disk_summ(nmon: &NmonData) -> String {
+    let filename = nmon.filename();
+    let mut res_nmon_text = String::new();
+    // 没有磁盘指标时(比如nmon未采集磁盘数据)不打印该表
+    let (Some(read), Some(write), Some(xfer)) =
+        (nmon.measurement("DISKREAD"), nmon.measurement("DISKWRITE"), nmon.measurement("DISKXFER"))
+    else {
+        return res_nmon_text;
+    };
+    let (read, write, xfer) = (read.sum_overtime(), write.sum_overtime(), xfer.sum_overtime());
+
+    let note = "Disk total";
+    let header = vec!["Disk Read KB/s", "Disk Write KB/s", "IO/sec"];
     let pheader = PrintData::new("FILENAME", "KEY", "NOTE", "TAG", header);
-    let note = cpu_all.note();
-    let pavg = PrintData::new(filename, measurement_name, note, "mean", mean);
-    let pstdev = PrintData::new(filename, measurement_name, note, "stdev", stdev);
+    let mean = vec![stats::mean(&read.to_vec()), stats::mean(&write.to_vec()), stats::mean(&xfer.to_vec())];
+    let max = vec![
+        read.iter().cloned().fold(0., f32::max),
+        write.iter().cloned().fold(0., f32::max),
+        xfer.iter().cloned().fold(0., f32::max),
+    ];
+    let pavg = PrintData::new(filename, "DISK_SUMM", note, "mean", mean);
+    let pmax = PrintData::new(filename, "DISK_SUMM", note, "max", max);
     let mut builder = tabled::builder::Builder::default();
     builder.push_record(pheader);
     builder.push_record(pavg);
-    builder.push_record(pstdev);
+    builder.push_record(pmax);
+    let table = builder.build().with(Style::rounded()).to_string();
+    println!("{}", table);
+    res_nmon_text.push_str(table.as_str());
+
+    res_nmon_text
+}
+
+pub fn console_print_top(nmon: &NmonData, top: usize) -> String {
+    let filename = nmon.filename();
+    let mut res_nmon_text = String::new();
+    let Some(processes) = nmon.top() else {
+        return res_nmon_text;
+    };
+
+    let mut builder = tabled::builder::Builder::default();
+    builder.push_record(["FILENAME", "COMMAND", "mean %CPU", "max %CPU", "max ResData MB", "FullCommand"]);
+    for (command, mean) in TopProcesses::top_by_mean(processes.cpu(), top) {
+        let max_cpu = processes.cpu().get(&command).unwrap().iter().cloned().fold(0., f32::max);
+        let max_res_data = processes.res_data().get(&command).unwrap().iter().cloned().fold(0., f32::max) / 1024.;
+        let full_command = processes
+            .full_commands(&command)
+            .map(|set| set.iter().cloned().collect::<Vec<String>>().join("\n"))
+            .unwrap_or_default();
+        builder.push_record([
+            filename.to_string(),
+            command,
+            mean.to_string(),
+            max_cpu.to_string(),
+            max_res_data.to_string(),
+            full_command,
+        ]);
+    }
+    let table = builder.build().with(Style::rounded()).to_string();
+    println!("{}", table);
+    res_nmon_text.push_str(table.as_str());
+
+    res_nmon_text
+}
+
+/// --lenient时各nmon文件跳过的数据，每个文件最多列出前10条
+pub fn console_print_diagnostics(nmon_datas: &[NmonData], skipped: &[NmonError]) -> String {
+    let mut res_nmon_text = String::new();
+    let mut builder = tabled::builder::Builder::default();
+    builder.push_record(["FILENAME", "SKIPPED", "DETAIL"]);
+    for ndata in nmon_datas.iter() {
+        let diagnostics = ndata.diagnostics();
+        let mut detail: Vec<String> = diagnostics.iter().take(10).map(|e| e.to_string()).collect();
+        if diagnostics.len() > 10 {
+            detail.push(format!("...共{}条", diagnostics.len()));
+        }
+        builder.push_record([ndata.filename().to_string(), diagnostics.len().to_string(), detail.join("\n")]);
+    }
+    for err in skipped.iter() {
+        builder.push_record(["-".to_string(), "整个文件".to_string(), err.to_string()]);
+    }
+    let table = builder.build().with(Style::rounded()).to_string();
+    println!("{}", table);
+    res_nmon_text.push_str(table.as_str());
+
+    res_nmon_text
+}
+
+/// 打印各nmon文件里的所有指标、列名及行数，ZZZZ的行数为快照数
+pub fn console_print_sections(files: &[FileSections]) -> String {
+    let mut res_nmon_text = String::new();
+    let mut builder = tabled::builder::Builder::default();
+    builder.push_record(["FILENAME", "SECTION", "NOTE", "ROWS", "COLUMNS", "HEADER"]);
+    for (filename, sections, snapshots) in files.iter() {
+        builder.push_record([filename.clone(), "ZZZZ".to_string(), "-".to_string(), snapshots.to_string(), "-".to_string(), "-".to_string()]);
+        for section in sections.iter() {
+            builder.push_record([
+                filename.clone(),
+                section.name.clone(),
+                section.note.clone(),
+                section.rows.to_string(),
+                section.header.len().to_string(),
+                section.header.join(","),
+            ]);
+        }
+    }
     let table = builder.build().with(Style::rounded()).to_string();
     println!("{}", table);
     res_nmon_text.push_str(table.as_str());
@@ -47,6 +202,61 @@ pub fn console_print_cpuall(nmon: &NmonData) -> String {
     res_nmon_text
 }
 
+/// 打印阈值规则的判断结果
+pub fn console_print_rules(results: &[RuleResult]) -> String {
+    let mut res_nmon_text = String::new();
+    let mut builder = tabled::builder::Builder::default();
+    builder.push_record(["FILENAME", "RULE", "ACTUAL", "RESULT"]);
+    for result in results.iter() {
+        let actual = match result.actual {
+            Some((ref column, value)) => format!("{} ({})", value, column),
+            None => "指标或列不存在，或没有数据".to_string(),
+        };
+        let passed = if result.passed { "PASS" } else { "FAIL" };
+        builder.push_record([result.filename.clone(), result.rule.clone(), actual, passed.to_string()]);
+    }
+    let table = builder.build().with(Style::rounded()).to_string();
+    println!("{}", table);
+    res_nmon_text.push_str(table.as_str());
+
+    res_nmon_text
+}
+
+/// 两次测试的对比结果，每行为一个事务或服务器的一个指标
+fn compare_builder(rows: &[DiffRow]) -> tabled::builder::Builder {
+    let mut builder = tabled::builder::Builder::default();
+    builder.push_record(["SOURCE", "NAME", "METRIC", "BASELINE", "CANDIDATE", "CHANGE", "RESULT"]);
+    for row in rows.iter() {
+        builder.push_record([
+            row.source.to_string(),
+            row.name.clone(),
+            row.metric.name().to_string(),
+            row.baseline_text(),
+            row.candidate_text(),
+            row.change_text(),
+            row.result_text().to_string(),
+        ]);
+    }
+    builder
+}
+
+/// 打印两次测试的对比结果
+pub fn console_print_compare(rows: &[DiffRow]) -> String {
+    let table = compare_builder(rows).build().with(Style::rounded()).to_string();
+    println!("{}", table);
+    table
+}
+
+/// Markdown格式的两次测试的对比结果
+pub fn markdown_compare(baseline: &str, candidate: &str, rows: &[DiffRow]) -> String {
+    let mut res_md = String::new();
+    res_md.push_str(&format!("### {} vs {}\n\n", baseline, candidate));
+    res_md.push_str(&format!("回归: {}\n\n", rows.iter().filter(|r| r.regression).count()));
+    res_md.push_str(&compare_builder(rows).build().with(Style::markdown()).to_string());
+    res_md.push('\n');
+    res_md
+}
+
 #[derive(Debug, Default)]
 struct PrintData<T>
 where
@@ -0,0 +1,189 @@
+use serde_derive::Deserialize;
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::askama::html::ChartsName;
+use crate::chart::ChartKind;
+use crate::chart::ChartSpec;
+
+/// 报告定义文件的内容
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ReportFile {
+    title: Option<String>,
+    #[serde(default)]
+    sections: Vec<String>,
+    #[serde(default, alias = "charts")]
+    chart: Vec<ChartDef>,
+}
+
+/// 一个图表: name为内置图表，或者section+columns+kind为自定义图表
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ChartDef {
+    name: Option<String>,
+    section: Option<String>,
+    columns: Option<Vec<String>>,
+    kind: Option<String>,
+    title: Option<String>,
+    unit: Option<String>,
+    #[serde(default)]
+    selected: bool,
+}
+
+/// 内置图表在报告定义文件里指定的标题和Y轴的单位，覆盖图表默认的标题和单位
+#[derive(Debug, Clone, Default)]
+pub struct ChartLabel {
+    pub title: Option<String>,
+    pub unit: Option<String>,
+}
+
+impl ChartDef {
+    /// 返回图表，以及内置图表指定的标题和单位
+    fn chart(self, i: usize) -> Result<((ChartsName, bool), Option<ChartLabel>), String> {
+        let name = match (self.name, self.section) {
+            (Some(name), None) => {
+                if self.columns.is_some() || self.kind.is_some() {
+                    return Err(format!("第{}个图表'{}'为内置图表，不能指定columns、kind", i, name));
+                }
+                let name = ChartsName::from_str(&name)?;
+                let label = (self.title.is_some() || self.unit.is_some()).then_some(ChartLabel { title: self.title, unit: self.unit });
+                return Ok(((name, self.selected), label));
+            },
+            (None, Some(section)) => {
+                let columns = self.columns.unwrap_or_else(|| vec!["*".to_string()]);
+                let columns: Vec<&str> = columns.iter().map(|c| c.as_str()).collect();
+                let kind = match self.kind {
+                    Some(ref kind) => ChartKind::from_str(kind)?,
+                    None => ChartKind::Line,
+                };
+                ChartsName::SPEC(ChartSpec::new(&section, &columns, kind)?.with_label(self.title, self.unit))
+            },
+            _ => return Err(format!("第{}个图表需要指定name(内置图表)或section(自定义图表)其中之一", i)),
+        };
+        Ok(((name, self.selected), None))
+    }
+}
+
+/// 报告定义: 生成哪些图表、图表的顺序和标题，以及额外需要解析的指标。
+/// 内置图表用name指定，比如: SYS_SUMM；自定义图表同--chart，用section、columns、kind指定
+#[derive(Debug, Clone)]
+pub struct Report {
+    title: Option<String>,
+    sections: Vec<String>,
+    charts: Vec<(ChartsName, bool)>,
+    // 内置图表的标题和单位，key为ChartsName::id()
+    labels: BTreeMap<String, ChartLabel>,
+}
+
+impl Report {
+    /// 读取报告定义文件，后缀为.json时按JSON解析，否则按TOML解析
+    pub fn read(path: &str) -> Result<Self, String> {
+        let path = Path::new(path);
+        let content = std::fs::read_to_string(path).map_err(|e| format!("{}读取失败: {}", path.display(), e))?;
+        Self::parse(path, &content)
+    }
+
+    fn parse(path: &Path, content: &str) -> Result<Self, String> {
+        let file: ReportFile = if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json")) {
+            serde_json::from_str(content).map_err(|e| format!("{}解析失败: {}", path.display(), e))?
+        } else {
+            toml::from_str(content).map_err(|e| format!("{}解析失败: {}", path.display(), e))?
+        };
+        if file.chart.is_empty() {
+            return Err(format!("{}没有定义图表", path.display()));
+        }
+        let mut charts = Vec::new();
+        let mut labels = BTreeMap::new();
+        for (i, chart) in file.chart.into_iter().enumerate() {
+            let (chart, label) = chart.chart(i + 1).map_err(|e| format!("{}: {}", path.display(), e))?;
+            if let Some(label) = label {
+                labels.insert(chart.0.id(), label);
+            }
+            charts.push(chart);
+        }
+        Ok(Self { title: file.title, sections: file.sections, charts, labels })
+    }
+
+    /// HTML报告的标题
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// 各图表需要的指标，以及sections里额外指定的指标
+    pub fn sections(&self) -> HashSet<String> {
+        let mut cell: HashSet<String> = self.sections.iter().cloned().collect();
+        for (name, _) in self.charts.iter() {
+            cell.extend(name.sections());
+        }
+        cell
+    }
+
+    /// 单个nmon文件的图表，按定义的顺序
+    pub fn charts(&self) -> Vec<(ChartsName, bool)> {
+        self.charts.iter().filter(|(name, _)| !name.is_compare()).cloned().collect()
+    }
+
+    /// 多个nmon文件的对比图，按定义的顺序
+    pub fn compares(&self) -> Vec<(ChartsName, bool)> {
+        self.charts.iter().filter(|(name, _)| name.is_compare()).cloned().collect()
+    }
+
+    /// 内置图表指定的标题和单位，key为ChartsName::id()
+    pub fn labels(&self) -> &BTreeMap<String, ChartLabel> {
+        &self.labels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_chart_title_and_unit() {
+        let toml = r#"
+            title = "数据库服务器"
+
+            [[chart]]
+            name = "SYS_SUMM"
+            selected = true
+            title = "系统概要"
+
+            [[chart]]
+            name = "MEM_FREE"
+            unit = "GB"
+
+            [[chart]]
+            name = "CPU_ALL"
+
+            [[chart]]
+            section = "MEM"
+            columns = ["memfree"]
+            title = "可用内存"
+            unit = "MB"
+        "#;
+        let report = Report::parse(Path::new("db.toml"), toml).unwrap();
+        assert_eq!(report.title(), Some("数据库服务器"));
+        assert_eq!(report.charts().len(), 4);
+        let labels = report.labels();
+        assert_eq!(labels.len(), 2);
+        assert_eq!(labels["SYS_SUMM"].title.as_deref(), Some("系统概要"));
+        assert!(labels["SYS_SUMM"].unit.is_none());
+        assert_eq!(labels["MEM_FREE"].unit.as_deref(), Some("GB"));
+        // 自定义图表的标题和单位在ChartSpec里
+        match &report.charts()[3].0 {
+            ChartsName::SPEC(spec) => assert_eq!((spec.title(), spec.unit()), (Some("可用内存"), Some("MB"))),
+            other => panic!("{}", other.to_string()),
+        }
+    }
+
+    #[test]
+    fn builtin_chart_rejects_columns() {
+        let toml = "[[chart]]\nname = \"SYS_SUMM\"\ncolumns = [\"User%\"]\n";
+        assert!(Report::parse(Path::new("db.toml"), toml).is_err());
+        let json = r#"{"chart": [{"name": "NOPE"}]}"#;
+        assert!(Report::parse(Path::new("db.json"), json).is_err());
+    }
+}
@@ -0,0 +1,324 @@
+use ndarray::Array1;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::Measurement;
+use crate::NmonData;
+use crate::stats;
+
+/// 规则不通过时rnmon的退出码，与解析出错(1)、参数错误(2)区分
+pub const RULE_FAILED_EXIT_CODE: i32 = 3;
+
+/// 规则里对时间序列取的统计值
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stat {
+    Mean,
+    Max,
+    Min,
+    Wavg,
+    Stdev,
+}
+
+impl FromStr for Stat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "mean" | "avg" => Ok(Stat::Mean),
+            "max" => Ok(Stat::Max),
+            "min" => Ok(Stat::Min),
+            "wavg" => Ok(Stat::Wavg),
+            "stdev" => Ok(Stat::Stdev),
+            _ => Err(format!("统计值'{}'不正确，只支持mean、max、min、wavg、stdev", s)),
+        }
+    }
+}
+
+impl Display for Stat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Stat::Mean => "mean",
+            Stat::Max => "max",
+            Stat::Min => "min",
+            Stat::Wavg => "wavg",
+            Stat::Stdev => "stdev",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl Stat {
+    /// 对某列或多列之和的时间序列取统计值，与Measurement::rows_*的算法一致
+    /// 没有数据时为None
+    fn of(&self, array1: &Array1<f32>) -> Option<f32> {
+        let values = array1.to_vec();
+        let value = match self {
+            Stat::Mean => stats::mean(&values),
+            Stat::Max => stats::max(&values),
+            Stat::Min => stats::min(&values),
+            Stat::Stdev => stats::stdev(&values),
+            Stat::Wavg => stats::wavg(&values),
+        };
+        Some(value).filter(|v| !v.is_nan())
+    }
+
+    /// 指标各列分别取统计值
+    fn rows(&self, measurement: &Measurement) -> Vec<f32> {
+        match self {
+            Stat::Mean => measurement.rows_mean(),
+            Stat::Max => measurement.rows_max(),
+            Stat::Min => measurement.rows_min(),
+            Stat::Wavg => measurement.rows_wavg(),
+            Stat::Stdev => measurement.rows_stdev(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Op {
+    fn check(&self, actual: f32, expected: f32) -> bool {
+        match self {
+            Op::Lt => actual < expected,
+            Op::Le => actual <= expected,
+            Op::Gt => actual > expected,
+            Op::Ge => actual >= expected,
+        }
+    }
+    /// 多列时最可能不通过的值，<、<=取最大值，>、>=取最小值
+    fn worse(&self, a: f32, b: f32) -> bool {
+        match self {
+            Op::Lt | Op::Le => a > b,
+            Op::Gt | Op::Ge => a < b,
+        }
+    }
+}
+
+impl Display for Op {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Op::Lt => "<",
+            Op::Le => "<=",
+            Op::Gt => ">",
+            Op::Ge => ">=",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// 规则作用的列
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    /// 所有列分别判断，比如: DISKBUSY:*
+    All,
+    /// 单列或多列每个时间点求和，比如: CPU_ALL:User%+Sys%
+    Sum(Vec<String>),
+}
+
+/// nmon指标的阈值规则，格式为: SECTION:COLUMN[+COLUMN...] STAT OP VALUE，比如:
+/// CPU_ALL:User%+Sys% mean < 70
+/// MEM:memfree min > 1024
+/// DISKBUSY:* max < 90
+/// 列名里可以有空格(比如JFSFILE的挂载点)，STAT OP VALUE从右边解析，其余部分为SECTION:COLUMN
+#[derive(Debug, Clone)]
+pub struct Rule {
+    raw: String,
+    section: String,
+    target: Target,
+    stat: Stat,
+    op: Op,
+    value: f32,
+}
+
+impl FromStr for Rule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let usage = || format!("规则'{}'格式不正确，应为: SECTION:COLUMN[+COLUMN...] STAT OP VALUE，比如: \"CPU_ALL:User%+Sys% mean < 70\"", s);
+        let (rest, value) = split_last(s).ok_or_else(usage)?;
+        let (rest, op) = split_last(rest).ok_or_else(usage)?;
+        let (target, stat) = split_last(rest).ok_or_else(usage)?;
+        let target = target.trim_start();
+        let raw = format!("{} {} {} {}", target, stat, op, value);
+        let (section, columns) = target.split_once(':').ok_or_else(usage)?;
+        if section.is_empty() || columns.is_empty() {
+            return Err(usage());
+        }
+        let target = if columns == "*" {
+            Target::All
+        } else {
+            Target::Sum(columns.split('+').map(|c| c.to_string()).collect())
+        };
+        let op = match op {
+            "<" => Op::Lt,
+            "<=" => Op::Le,
+            ">" => Op::Gt,
+            ">=" => Op::Ge,
+            _ => return Err(format!("规则'{}'的比较符'{}'不正确，只支持<、<=、>、>=", s, op)),
+        };
+        let value = f32::from_str(value).map_err(|_| format!("规则'{}'的阈值'{}'不是数字", s, value))?;
+        Ok(Self {
+            raw,
+            section: section.to_string(),
+            target,
+            stat: Stat::from_str(stat)?,
+            op,
+            value,
+        })
+    }
+}
+
+/// 从右边取出以空白分隔的最后一个词，返回(其余部分, 该词)
+fn split_last(s: &str) -> Option<(&str, &str)> {
+    let (rest, last) = s.trim_end().rsplit_once(char::is_whitespace)?;
+    Some((rest.trim_end(), last))
+}
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.raw)
+    }
+}
+
+/// 规则对某个nmon文件的判断结果
+pub struct RuleResult {
+    pub filename: String,
+    pub rule: String,
+    /// 实际的统计值，多列时为最可能不通过的列
+    pub actual: Option<(String, f32)>,
+    pub passed: bool,
+}
+
+impl Rule {
+    pub fn section(&self) -> &str {
+        &self.section
+    }
+
+    /// 指标或列不存在、没有数据时，判定为不通过
+    pub fn evaluate(&self, ndata: &NmonData) -> RuleResult {
+        let actual = ndata.measurement(&self.section).and_then(|measurement| match &self.target {
+            Target::All => measurement
+                .header()
+                .iter()
+                .cloned()
+                .zip(self.stat.rows(measurement))
+                .filter(|(_, v)| !v.is_nan())
+                .reduce(|a, b| if self.op.worse(b.1, a.1) { b } else { a }),
+            Target::Sum(columns) => {
+                let mut sum: Option<Array1<f32>> = None;
+                for column in columns.iter() {
+                    let array1 = measurement.column(column)?;
+                    sum = Some(match sum {
+                        Some(sum) => sum + array1,
+                        None => array1,
+                    });
+                }
+                Some((columns.join("+"), self.stat.of(&sum?)?))
+            }
+        });
+        let passed = actual.as_ref().is_some_and(|(_, v)| self.op.check(*v, self.value));
+        RuleResult {
+            filename: ndata.filename().to_string(),
+            rule: self.raw.clone(),
+            actual,
+            passed,
+        }
+    }
+}
+
+/// 从文件读取规则，每行一个规则，忽略空行和#开头的注释
+pub fn read_rules(path: &std::path::Path) -> Result<Vec<Rule>, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| format!("{}读取失败: {}", path.display(), e))?;
+    content
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(Rule::from_str)
+        .collect()
+}
+
+/// 所有规则对所有nmon文件的判断结果
+pub fn evaluate(rules: &[Rule], nmon_datas: &[NmonData]) -> Vec<RuleResult> {
+    nmon_datas
+        .iter()
+        .flat_map(|ndata| rules.iter().map(move |rule| rule.evaluate(ndata)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ParseOptions;
+    use std::collections::HashSet;
+    use std::io::Cursor;
+
+    fn ndata() -> NmonData {
+        let nmon = "CPU_ALL,CPU Total app01,User%,Sys%,Idle%\n\
+                    JFSFILE,JFS Filespace %Used app01,/,/data disk\n\
+                    ZZZZ,T0001,17:40:01,16-JUN-2020\n\
+                    CPU_ALL,T0001,40.0,10.0,50.0\n\
+                    JFSFILE,T0001,35.0,80.0\n\
+                    ZZZZ,T0002,17:40:02,16-JUN-2020\n\
+                    CPU_ALL,T0002,60.0,20.0,20.0\n\
+                    JFSFILE,T0002,36.0,90.0\n";
+        let cell: HashSet<String> = ["CPU_ALL".to_string(), "JFSFILE".to_string()].into_iter().collect();
+        NmonData::from_reader(Cursor::new(nmon), "app01.nmon", &cell, &ParseOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn parse_rule() {
+        let rule = Rule::from_str("  CPU_ALL:User%+Sys%   mean <  70 ").unwrap();
+        assert_eq!(rule.section(), "CPU_ALL");
+        assert_eq!(rule.target, Target::Sum(vec!["User%".to_string(), "Sys%".to_string()]));
+        assert_eq!(rule.stat, Stat::Mean);
+        assert_eq!(rule.op, Op::Lt);
+        assert_eq!(rule.value, 70.);
+        assert_eq!(rule.to_string(), "CPU_ALL:User%+Sys% mean < 70");
+
+        let rule = Rule::from_str("DISKBUSY:* max >= 90").unwrap();
+        assert_eq!(rule.target, Target::All);
+        assert_eq!(rule.op, Op::Ge);
+    }
+
+    #[test]
+    fn parse_rule_column_with_spaces() {
+        let rule = Rule::from_str("JFSFILE:/data disk max < 95").unwrap();
+        assert_eq!(rule.section(), "JFSFILE");
+        assert_eq!(rule.target, Target::Sum(vec!["/data disk".to_string()]));
+        assert_eq!(rule.stat, Stat::Max);
+    }
+
+    #[test]
+    fn parse_rule_errors() {
+        assert!(Rule::from_str("CPU_ALL:User% mean <").is_err());
+        assert!(Rule::from_str("CPU_ALL mean < 70").is_err());
+        assert!(Rule::from_str(":User% mean < 70").is_err());
+        assert!(Rule::from_str("CPU_ALL:User% median < 70").is_err());
+        assert!(Rule::from_str("CPU_ALL:User% mean == 70").is_err());
+        assert!(Rule::from_str("CPU_ALL:User% mean < high").is_err());
+    }
+
+    #[test]
+    fn evaluate_rules() {
+        let ndata = ndata();
+        let passed = |rule: &str| Rule::from_str(rule).unwrap().evaluate(&ndata).passed;
+        // User%+Sys%: 50、80，mean为65
+        assert!(passed("CPU_ALL:User%+Sys% mean < 70"));
+        assert!(!passed("CPU_ALL:User%+Sys% max < 70"));
+        assert!(passed("CPU_ALL:Idle% min >= 20"));
+        assert!(passed("JFSFILE:/data disk max <= 90"));
+        // 各列分别判断，/data disk的max为90
+        assert!(!passed("JFSFILE:* max < 90"));
+        let result = Rule::from_str("JFSFILE:* max < 90").unwrap().evaluate(&ndata);
+        assert_eq!(result.actual, Some(("/data disk".to_string(), 90.)));
+        // 指标或列不存在时不通过
+        assert!(!passed("MEM:memfree min > 0"));
+        assert!(!passed("CPU_ALL:Steal% max < 10"));
+    }
+}
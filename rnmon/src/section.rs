@@ -0,0 +1,209 @@
+use globset::Glob;
+use globset::GlobSet;
+use globset::GlobSetBuilder;
+use log::debug;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io::BufRead;
+
+use crate::error::NmonError;
+
+/// 各CPU的指标: CPU01、CPU001、CPU1024等，不包括CPU_ALL
+pub const CPU_SECTIONS: &str = "CPU[0-9]*";
+
+/// 要解析的指标，可以是指标名称(比如: CPU_ALL、MEM)，也可以是glob(比如: CPU[0-9]*、DISK*、*)。
+/// glob不匹配文件头(AAA、BBB*)和ZZZZ时间序列，这些由解析器单独处理
+#[derive(Debug, Clone)]
+pub struct SectionMatcher {
+    names: HashSet<String>,
+    patterns: GlobSet,
+    // 每个指标名称只匹配一次，nmon文件的每一行都要判断
+    cache: HashMap<String, bool>,
+}
+
+impl SectionMatcher {
+    pub fn new(cell: &HashSet<String>) -> Self {
+        let mut names = HashSet::new();
+        let mut builder = GlobSetBuilder::new();
+        for section in cell.iter() {
+            // 不是合法的glob时按指标名称处理
+            match Glob::new(section) {
+                Ok(glob) if is_pattern(section) => {
+                    builder.add(glob);
+                },
+                _ => {
+                    names.insert(section.clone());
+                },
+            }
+        }
+        Self {
+            names,
+            patterns: builder.build().unwrap_or_else(|_| GlobSet::empty()),
+            cache: HashMap::new(),
+        }
+    }
+
+    /// 是否按名称选择了该指标，而不是只被glob匹配
+    pub fn is_named(&self, name: &str) -> bool {
+        self.names.contains(name)
+    }
+
+    /// 是否要解析该指标
+    pub fn is_selected(&mut self, name: &str) -> bool {
+        if let Some(selected) = self.cache.get(name) {
+            return *selected;
+        }
+        let selected = self.names.contains(name) || (is_data_section(name) && self.patterns.is_match(name));
+        if !selected {
+            debug!("指标{}未选择，跳过", name);
+        }
+        self.cache.insert(name.to_string(), selected);
+        selected
+    }
+}
+
+/// 指标名称里是否有glob的特殊字符
+fn is_pattern(section: &str) -> bool {
+    section.contains(['*', '?', '[', '{'])
+}
+
+/// 是否为指标数据，而不是文件头或时间序列
+fn is_data_section(name: &str) -> bool {
+    !name.is_empty() && name != "ZZZZ" && !name.starts_with("AAA") && !name.starts_with("BBB")
+}
+
+/// nmon文件里的一个指标: 列名为第一行的[2..]，行数为之后的数据行数
+#[derive(Debug, Clone)]
+pub struct SectionInfo {
+    pub name: String,
+    /// 列名行的第二列，指标的说明，比如: CPU Total app01
+    pub note: String,
+    pub header: Vec<String>,
+    pub rows: usize,
+}
+
+/// 一个nmon文件里的所有指标: (nmon文件名, 各指标, ZZZZ快照数)
+pub type FileSections = (String, Vec<SectionInfo>, usize);
+
+/// 扫描nmon文件里的所有指标，按在文件里第一次出现的顺序，不解析数据。
+/// 返回各指标，以及ZZZZ快照数
+pub fn scan<R: BufRead>(br: R, filename: &str) -> Result<(Vec<SectionInfo>, usize), NmonError> {
+    let mut sections: Vec<SectionInfo> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    let mut snapshots = 0;
+    for (idx, line) in br.lines().enumerate() {
+        let line = line.map_err(|e| NmonError::Line { file: filename.to_string(), line: idx + 1, source: e })?;
+        let vec: Vec<&str> = line.split(',').collect();
+        let name = vec[0];
+        if name == "ZZZZ" {
+            snapshots += 1;
+            continue;
+        }
+        if !is_data_section(name) || vec.len() < 2 {
+            continue;
+        }
+        // TOP、UARG的列名行为第二列以+开头的行: TOP,+PID,Time,...、UARG,+Time,PID,...，TOP的第一行为说明
+        if (name == "TOP" || name == "UARG") && vec[1].starts_with('+') {
+            let header = vec[1..].iter().map(|h| h.trim_start_matches('+').to_string()).collect();
+            match index.get(name) {
+                Some(i) => sections[*i].header = header,
+                None => {
+                    index.insert(name.to_string(), sections.len());
+                    sections.push(SectionInfo { name: name.to_string(), note: String::new(), header, rows: 0 });
+                },
+            }
+            continue;
+        }
+        match index.get(name) {
+            Some(i) => sections[*i].rows += 1,
+            None => {
+                index.insert(name.to_string(), sections.len());
+                sections.push(SectionInfo {
+                    name: name.to_string(),
+                    note: vec[1].to_string(),
+                    header: vec[2..].iter().map(|h| h.to_string()).collect(),
+                    rows: 0,
+                });
+            },
+        }
+    }
+    Ok((sections, snapshots))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NmonData;
+    use crate::ParseOptions;
+    use std::io::Cursor;
+
+    /// 20个快照，NETERROR只有列名行
+    fn fixture() -> String {
+        let mut nmon = String::from(
+            "AAA,host,app01\n\
+             CPU_ALL,CPU Total app01,User%,Sys%,Wait%,Idle%\n\
+             MEM,Memory MB app01,memtotal,memfree\n\
+             NETERROR,Network Errors app01,eth0-ierrs,eth0-oerrs\n",
+        );
+        for i in 1..=20 {
+            nmon.push_str(&format!("ZZZZ,T{:04},17:40:{:02},16-JUN-2020\n", i, i));
+            nmon.push_str(&format!("CPU_ALL,T{:04},10.0,5.0,1.0,84.0\n", i));
+            nmon.push_str(&format!("MEM,T{:04},2048,1024\n", i));
+        }
+        nmon
+    }
+
+    fn parse(sections: &[&str]) -> NmonData {
+        let cell: HashSet<String> = sections.iter().map(|s| s.to_string()).collect();
+        NmonData::from_reader(Cursor::new(fixture()), "app01.nmon", &cell, &ParseOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn glob_skips_sections_without_rows() {
+        let ndata = parse(&["*"]);
+        assert!(ndata.measurement("NETERROR").is_none());
+        assert_eq!(ndata.measurement("CPU_ALL").unwrap().data().nrows(), 20);
+        assert_eq!(ndata.measurement("MEM").unwrap().data().nrows(), 20);
+    }
+
+    #[test]
+    fn named_section_without_rows_is_skipped() {
+        let ndata = parse(&["CPU_ALL", "MEM", "NETERROR"]);
+        assert!(ndata.measurement("NETERROR").is_none());
+        assert_eq!(ndata.measurement("CPU_ALL").unwrap().data().nrows(), 20);
+    }
+
+    #[test]
+    fn scan_top_and_uarg_headers() {
+        let nmon = "ZZZZ,T0001,17:40:01,16-JUN-2020\n\
+                    TOP,%CPU Utilisation\n\
+                    TOP,+PID,Time,%CPU,ResData,Command\n\
+                    TOP,0001234,T0001,5.18,394957,java\n\
+                    TOP,0001235,T0001,1.20,706000,nginx\n\
+                    UARG,+Time,PID,PPID,COMM,FullCommand\n\
+                    UARG,T0001,1234,1,java,java -jar app.jar\n";
+        let (sections, snapshots) = scan(Cursor::new(nmon), "app01.nmon").unwrap();
+        assert_eq!(snapshots, 1);
+        let top = sections.iter().find(|s| s.name == "TOP").unwrap();
+        assert_eq!(top.note, "%CPU Utilisation");
+        assert_eq!(top.header, ["PID", "Time", "%CPU", "ResData", "Command"]);
+        assert_eq!(top.rows, 2);
+        let uarg = sections.iter().find(|s| s.name == "UARG").unwrap();
+        assert_eq!(uarg.note, "");
+        assert_eq!(uarg.header, ["Time", "PID", "PPID", "COMM", "FullCommand"]);
+        assert_eq!(uarg.rows, 1);
+    }
+
+    #[test]
+    fn glob_matches_data_sections_only() {
+        let mut matcher = SectionMatcher::new(&["CPU[0-9]*".to_string(), "MEM".to_string()].into_iter().collect());
+        assert!(matcher.is_selected("CPU001"));
+        assert!(matcher.is_selected("CPU1024"));
+        assert!(matcher.is_selected("MEM"));
+        assert!(!matcher.is_selected("CPU_ALL"));
+        let mut all = SectionMatcher::new(&["*".to_string()].into_iter().collect());
+        assert!(!all.is_selected("ZZZZ"));
+        assert!(!all.is_selected("BBBP"));
+        assert!(all.is_selected("NETERROR"));
+    }
+}
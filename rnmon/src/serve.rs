@@ -0,0 +1,266 @@
+use askama::Template;
+use anyhow::Result;
+use anyhow::anyhow;
+use log::debug;
+use log::info;
+use log::warn;
+use regex::Regex;
+use std::fs::File;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use tiny_http::Header;
+use tiny_http::Response;
+use tiny_http::Server;
+
+/// rmeter的测试结果目录，比如: 0330-1752_1u_0qps0ms0err、1122-1430_1qps2ms0err_test1
+static RUN_FOLDER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\d{4}-\d{4})_(?:(\d+)u_)?(\d+)qps(\d+)ms(\d+)err(?:_(.+))?$").unwrap());
+
+/// 查找测试结果目录时最多向下扫描的层数
+const MAX_DEPTH: usize = 3;
+
+#[derive(Template)]
+#[template(path = "serve_index.html.jinja2")]
+struct ServeIndex {
+    roots: Vec<RootRuns>,
+}
+
+struct RootRuns {
+    name: String,
+    runs: Vec<Run>,
+}
+
+/// 一次测试的结果目录，目录名不是rmeter的格式时，只显示目录名
+struct Run {
+    name: String,
+    time: String,
+    threads: String,
+    qps: String,
+    ms: String,
+    err: String,
+    note: String,
+    // (报告名称, 链接)
+    links: Vec<(String, String)>,
+}
+
+impl Run {
+    /// 有nmon图表、JMeter报告或rmeter格式的目录名时才是测试结果目录
+    fn new(dir: &Path, href: &str) -> Option<Self> {
+        let name = dir.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_else(|| ".".to_string());
+        let mut links = Vec::new();
+        for (title, file) in [
+            ("nmon", "index_nmons.html"),
+            ("jmeter", "res/index.html"),
+            ("res.nmon.txt", "res.nmon.txt"),
+            ("res.nmon.md", "res.nmon.md"),
+            ("res.rules.txt", "res.rules.txt"),
+            ("compare", "res.compare.html"),
+        ] {
+            if dir.join(file).is_file() {
+                links.push((title.to_string(), format!("{}{}", href, file)));
+            }
+        }
+        let caps = RUN_FOLDER.captures(&name);
+        if links.is_empty() && caps.is_none() {
+            return None;
+        }
+        let cap = |i: usize| caps.as_ref().and_then(|c| c.get(i)).map(|m| m.as_str().to_string()).unwrap_or_default();
+        Some(Self {
+            time: cap(1),
+            threads: cap(2),
+            qps: cap(3),
+            ms: cap(4),
+            err: cap(5),
+            note: cap(6),
+            name,
+            links,
+        })
+    }
+}
+
+/// 扫描root下的测试结果目录，按目录名倒序(最近的测试在前)
+fn scan_runs(root: &Path, prefix: &str) -> Vec<Run> {
+    let mut runs = Vec::new();
+    scan_dir(root, prefix, 0, &mut runs);
+    runs.sort_by(|a, b| b.name.cmp(&a.name));
+    runs
+}
+
+fn scan_dir(dir: &Path, href: &str, depth: usize, runs: &mut Vec<Run>) {
+    if let Some(run) = Run::new(dir, href) {
+        runs.push(run);
+        return;
+    }
+    if depth >= MAX_DEPTH {
+        return;
+    }
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        warn!("{}目录读取失败", dir.display());
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            scan_dir(&path, &format!("{}{}/", href, percent_encode(&name)), depth + 1, runs);
+        }
+    }
+}
+
+fn percent_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut res = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && i + 3 <= bytes.len()
+            && let Some(b) = std::str::from_utf8(&bytes[i + 1..i + 3]).ok().and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            res.push(b);
+            i += 3;
+            continue;
+        }
+        res.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&res).to_string()
+}
+
+fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|ext| ext.to_str()).unwrap_or_default() {
+        "html" | "htm" => "text/html; charset=utf-8",
+        "js" => "application/javascript; charset=utf-8",
+        "css" => "text/css; charset=utf-8",
+        "json" => "application/json; charset=utf-8",
+        "txt" | "md" | "csv" | "log" | "jtl" | "nmon" => "text/plain; charset=utf-8",
+        "png" => "image/png",
+        "svg" => "image/svg+xml",
+        "ico" => "image/x-icon",
+        _ => "application/octet-stream",
+    }
+}
+
+fn header(value: &str) -> Header {
+    Header::from_bytes(&b"Content-Type"[..], value.as_bytes()).unwrap()
+}
+
+/// 在本地端口上提供测试结果目录的报告，首页列出各root下的测试结果目录，
+/// 各root的文件通过/0/、/1/...访问，比如: /0/0330-1752_1u_0qps0ms0err/index_nmons.html
+pub struct ReportServer {
+    roots: Vec<PathBuf>,
+}
+
+impl ReportServer {
+    pub fn new(roots: Vec<PathBuf>) -> Result<Self> {
+        let roots = roots
+            .iter()
+            .map(|root| root.canonicalize().map_err(|e| anyhow!("{}目录读取失败: {}", root.display(), e)))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { roots })
+    }
+
+    fn index(&self) -> String {
+        let roots = self
+            .roots
+            .iter()
+            .enumerate()
+            .map(|(i, root)| RootRuns {
+                name: root.display().to_string(),
+                runs: scan_runs(root, &format!("/{}/", i)),
+            })
+            .collect();
+        ServeIndex { roots }.render().unwrap()
+    }
+
+    /// 把url转换为root下的文件或目录，不允许访问root以外的文件
+    fn resolve(&self, url: &str) -> Option<PathBuf> {
+        let path = percent_decode(url.split(['?', '#']).next().unwrap_or_default());
+        let mut parts = path.trim_start_matches('/').splitn(2, '/');
+        let root = self.roots.get(parts.next()?.parse::<usize>().ok()?)?;
+        let relative = Path::new(parts.next().unwrap_or_default());
+        if relative.components().any(|c| !matches!(c, Component::Normal(_))) {
+            return None;
+        }
+        let path = root.join(relative).canonicalize().ok()?;
+        if path.starts_with(root) { Some(path) } else { None }
+    }
+
+    /// 阻塞运行，直到Ctrl+C
+    pub fn run(&self, addr: &str) -> Result<()> {
+        let server = Server::http(addr).map_err(|e| anyhow!("{}监听失败: {}", addr, e))?;
+        info!("打开 http://{}/ 查看测试结果，Ctrl+C退出", addr);
+        for request in server.incoming_requests() {
+            let url = request.url().to_string();
+            debug!("{} {}", request.method(), url);
+            let res = if url == "/" || url.starts_with("/?") || url == "/index.html" {
+                request.respond(Response::from_string(self.index()).with_header(header("text/html; charset=utf-8")))
+            } else {
+                match self.resolve(&url) {
+                    // 目录需以/结尾，否则index_nmons.html里的相对路径./echarts.min.js指向上级目录
+                    Some(path) if path.is_dir() && !url.ends_with('/') => request.respond(
+                        Response::empty(301).with_header(Header::from_bytes(&b"Location"[..], format!("{}/", url).as_bytes()).unwrap()),
+                    ),
+                    Some(path) => {
+                        let path = if path.is_dir() { path.join("index_nmons.html") } else { path };
+                        match File::open(&path) {
+                            Ok(f) if path.is_file() => request.respond(Response::from_file(f).with_header(header(content_type(&path)))),
+                            _ => request.respond(Response::from_string("404 Not Found").with_status_code(404)),
+                        }
+                    }
+                    None => request.respond(Response::from_string("404 Not Found").with_status_code(404)),
+                }
+            };
+            if let Err(e) = res {
+                warn!("{}响应失败: {}", url, e);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_percent() {
+        assert_eq!(percent_decode("/0/a%20b"), "/0/a b");
+        assert_eq!(percent_decode("%E6%B5%8B%E8%AF%95"), "测试");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%2"), "%2");
+        assert_eq!(percent_decode("%zz"), "%zz");
+    }
+
+    #[test]
+    fn resolve_inside_root_only() {
+        let dir = std::env::temp_dir().join(format!("rnmon_serve_{}", std::process::id()));
+        let root = dir.join("root");
+        std::fs::create_dir_all(root.join("run 1")).unwrap();
+        std::fs::write(root.join("run 1").join("index_nmons.html"), "").unwrap();
+        std::fs::write(dir.join("secret.txt"), "").unwrap();
+        let server = ReportServer::new(vec![root.clone()]).unwrap();
+        let root = root.canonicalize().unwrap();
+
+        assert_eq!(server.resolve("/0/run%201/index_nmons.html?t=1"), Some(root.join("run 1").join("index_nmons.html")));
+        assert_eq!(server.resolve("/0/run%201"), Some(root.join("run 1")));
+        assert_eq!(server.resolve("/0/"), Some(root.clone()));
+        assert_eq!(server.resolve("/0/../secret.txt"), None);
+        assert_eq!(server.resolve("/0/%2e%2e/secret.txt"), None);
+        assert_eq!(server.resolve("/0/run%201/..%2f..%2fsecret.txt"), None);
+        assert_eq!(server.resolve("/0//etc/passwd"), None);
+        assert_eq!(server.resolve("/1/run%201"), None);
+        assert_eq!(server.resolve("/0/missing"), None);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,72 @@
+//! 时间序列的统计值，汇总表、阈值规则和Avg/WAvg/Max/Min图表使用相同的算法。
+//! 没有数据时(比如只有列名行的指标，或watch刚开始时)为NaN，而不是panic。
+//! --lenient时无法解析的值为NaN，统计时忽略
+
+pub(crate) fn mean(values: &[f32]) -> f32 {
+    mean_of(valid(values))
+}
+
+pub(crate) fn max(values: &[f32]) -> f32 {
+    valid(values).reduce(f32::max).unwrap_or(f32::NAN)
+}
+
+pub(crate) fn min(values: &[f32]) -> f32 {
+    valid(values).reduce(f32::min).unwrap_or(f32::NAN)
+}
+
+/// 总体标准差
+pub(crate) fn stdev(values: &[f32]) -> f32 {
+    let mean = mean(values);
+    mean_of(valid(values).map(|v| (v - mean) * (v - mean))).sqrt()
+}
+
+/// 以值本身为权重的加权平均: sum(x*x) / sum(x)，nmon analyser的WAvg.
+pub(crate) fn wavg(values: &[f32]) -> f32 {
+    let mean = mean(values);
+    if mean == 0. {
+        return 0.;
+    }
+    valid(values).map(|v| v * v).sum::<f32>() / valid(values).sum::<f32>()
+}
+
+fn valid(values: &[f32]) -> impl Iterator<Item = f32> + '_ {
+    values.iter().copied().filter(|v| !v.is_nan())
+}
+
+fn mean_of(values: impl Iterator<Item = f32>) -> f32 {
+    let (sum, n) = values.fold((0., 0), |(sum, n), v| (sum + v, n + 1));
+    if n == 0 { f32::NAN } else { sum / n as f32 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_is_nan() {
+        for stat in [mean, max, min, stdev, wavg] {
+            assert!(stat(&[]).is_nan());
+        }
+    }
+
+    #[test]
+    fn stats_of_values() {
+        let values = [1., 2., 3., 4.];
+        assert_eq!(mean(&values), 2.5);
+        assert_eq!(max(&values), 4.);
+        assert_eq!(min(&values), 1.);
+        assert!((stdev(&values) - 1.118034).abs() < 1e-5);
+        assert_eq!(wavg(&values), 3.);
+        assert_eq!(wavg(&[0., 0.]), 0.);
+    }
+
+    #[test]
+    fn nan_is_skipped() {
+        let values = [1., f32::NAN, 2., 3., 4.];
+        assert_eq!(mean(&values), 2.5);
+        assert_eq!(max(&values), 4.);
+        assert_eq!(min(&values), 1.);
+        assert_eq!(wavg(&values), 3.);
+        assert!(mean(&[f32::NAN]).is_nan());
+    }
+}
@@ -0,0 +1,311 @@
+use log::trace;
+use log::warn;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::str::FromStr;
+use chrono::DateTime;
+use chrono::FixedOffset;
+
+use crate::askama::Point;
+use crate::askama::POINT_X_FORMAT;
+use crate::downsample;
+use crate::error::NmonError;
+
+// TOP,%CPU Utilisation
+// TOP,+PID,Time,%CPU,%Usr,%Sys,Size,ResSet,ResText,ResData,ShdLib,MinorFault,MajorFault,Command
+// TOP,0001234,T0001,104.43,98.41,6.02,5418540,2012344,4,4954192,21676,151,0,java
+// ...
+// UARG,+Time,PID,PPID,COMM,THCOUNT,USER,GROUP,FullCommand
+// UARG,T0001,1234,1,java,50,app,app,/usr/bin/java -Xmx2g -jar app.jar
+//
+// TOP、UARG的数据行与其他指标不同，第二列为PID，第三列才是时间序列，且每个时间序列有多行(每个进程一行)，
+// 故单独解析，按进程的命令名称(Command)把同一时间序列的多个进程的数据相加
+#[derive(Clone)]
+pub struct TopBuilder {
+    filename: String,
+    header: Vec<String>,
+    uarg_header: Vec<String>,
+    cpu: BTreeMap<String, Vec<f32>>,
+    res_data: BTreeMap<String, Vec<f32>>,
+    commands: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl TopBuilder {
+    pub fn new(filename: &str) -> Self {
+        Self {
+            filename: filename.to_string(),
+            header: Vec::new(),
+            uarg_header: Vec::new(),
+            cpu: BTreeMap::new(),
+            res_data: BTreeMap::new(),
+            commands: BTreeMap::new(),
+        }
+    }
+
+    fn idx_of(header: &[String], name: &str) -> Option<usize> {
+        header.iter().position(|h| h == name)
+    }
+
+    fn value(&self, vec: &[&str], idx: usize, lineno: usize) -> Result<f32, NmonError> {
+        match vec.get(idx) {
+            Some(e) if !e.is_empty() => f32::from_str(e).map_err(|_| NmonError::Value {
+                file: self.filename.clone(),
+                line: lineno,
+                section: "TOP".to_string(),
+                column: self.header[idx].clone(),
+                value: e.to_string(),
+            }),
+            _ => Ok(0.0),
+        }
+    }
+
+    /// snapshots: ZZZZ时间序列标识(T0001)与其在ZZZZ时间序列中的下标
+    /// 数据无法转换为数字时返回错误，该行不计入
+    pub fn push_top(&mut self, vec: &[&str], line: &str, lineno: usize, snapshots: &HashMap<String, usize>) -> Result<(), NmonError> {
+        // TOP,%CPU Utilisation
+        if vec.len() < 3 {
+            return Ok(());
+        }
+        if vec[1] == "+PID" {
+            self.header = vec.iter().map(|e| e.to_string()).collect();
+            trace!("[{}]文件, [TOP]指标数据header头转换为数组: {:?}", self.filename, self.header);
+            return Ok(());
+        }
+        let (Some(idx_cpu), Some(idx_res_data), Some(idx_command)) = (
+            Self::idx_of(&self.header, "%CPU"),
+            Self::idx_of(&self.header, "ResData"),
+            Self::idx_of(&self.header, "Command"),
+        ) else {
+            warn!("[{}]文件, [TOP]指标数据在header头之前或header头缺少%CPU、ResData、Command列，抛弃它：{}", self.filename, line);
+            return Ok(());
+        };
+        let Some(idx) = snapshots.get(vec[2]) else {
+            warn!("[{}]文件, [TOP]指标数据的时间序列{}在ZZZZ中不存在，抛弃它：{}", self.filename, vec[2], line);
+            return Ok(());
+        };
+        let Some(command) = vec.get(idx_command) else {
+            warn!("[{}]文件, [TOP]指标数据长度小于列名长度，数据不完成，抛弃它：{}", self.filename, line);
+            return Ok(());
+        };
+        let cpu = self.value(vec, idx_cpu, lineno)?;
+        let res_data = self.value(vec, idx_res_data, lineno)?;
+
+        let values = self.cpu.entry(command.to_string()).or_default();
+        if values.len() <= *idx {
+            values.resize(*idx + 1, 0.0);
+        }
+        values[*idx] += cpu;
+        let values = self.res_data.entry(command.to_string()).or_default();
+        if values.len() <= *idx {
+            values.resize(*idx + 1, 0.0);
+        }
+        values[*idx] += res_data;
+        Ok(())
+    }
+
+    pub fn push_uarg(&mut self, vec: &[&str], line: &str) {
+        if vec.len() < 3 {
+            return;
+        }
+        if vec[1] == "+Time" {
+            self.uarg_header = vec.iter().map(|e| e.to_string()).collect();
+            return;
+        }
+        let (Some(idx_comm), Some(idx_full)) = (
+            Self::idx_of(&self.uarg_header, "COMM"),
+            Self::idx_of(&self.uarg_header, "FullCommand"),
+        ) else {
+            warn!("[{}]文件, [UARG]指标数据在header头之前或header头缺少COMM、FullCommand列，抛弃它：{}", self.filename, line);
+            return;
+        };
+        if vec.len() <= idx_full {
+            warn!("[{}]文件, [UARG]指标数据长度小于列名长度，数据不完成，抛弃它：{}", self.filename, line);
+            return;
+        }
+        // FullCommand里可能包含逗号
+        let full = vec[idx_full..].join(",");
+        self.commands.entry(vec[idx_comm].to_string()).or_default().insert(full);
+    }
+
+    /// max_points: 图表里每条曲线最多的点数
+    pub fn build(self, zzzz: &[DateTime<FixedOffset>], max_points: Option<usize>) -> TopProcesses {
+        // 某进程在部分时间序列里不存在时，其值为0
+        let fit = |map: BTreeMap<String, Vec<f32>>| -> BTreeMap<String, Vec<f32>> {
+            map.into_iter()
+                .map(|(k, mut v)| {
+                    v.resize(zzzz.len(), 0.0);
+                    (k, v)
+                })
+                .collect()
+        };
+        TopProcesses {
+            zzzz: zzzz.to_vec(),
+            cpu: fit(self.cpu),
+            res_data: fit(self.res_data),
+            commands: self.commands,
+            max_points,
+        }
+    }
+}
+
+
+/// nmon TOP指标，按命令名称汇总后的各时间序列的%CPU和ResData(KB)
+pub struct TopProcesses {
+    zzzz: Vec<DateTime<FixedOffset>>,
+    cpu: BTreeMap<String, Vec<f32>>,
+    res_data: BTreeMap<String, Vec<f32>>,
+    commands: BTreeMap<String, BTreeSet<String>>,
+    // 图表里每条曲线最多的点数，超过时降采样
+    max_points: Option<usize>,
+}
+
+impl TopProcesses {
+    pub fn is_empty(&self) -> bool {
+        self.cpu.is_empty()
+    }
+    pub fn cpu(&self) -> &BTreeMap<String, Vec<f32>> {
+        &self.cpu
+    }
+    pub fn res_data(&self) -> &BTreeMap<String, Vec<f32>> {
+        &self.res_data
+    }
+    /// UARG里该命令名称对应的完整命令行
+    pub fn full_commands(&self, command: &str) -> Option<&BTreeSet<String>> {
+        self.commands.get(command)
+    }
+
+    /// 只保留某时间段(ZZZZ时间序列下标范围)内的数据，时间段内都为0的命令也去掉
+    pub fn retain_rows(&mut self, range: Range<usize>) {
+        let slice = |map: &mut BTreeMap<String, Vec<f32>>| {
+            for values in map.values_mut() {
+                *values = values[range.clone()].to_vec();
+            }
+        };
+        slice(&mut self.cpu);
+        slice(&mut self.res_data);
+        self.zzzz = self.zzzz[range.clone()].to_vec();
+        let idle: Vec<String> = self
+            .cpu
+            .iter()
+            .filter(|(k, v)| v.iter().all(|e| *e == 0.0) && self.res_data.get(*k).is_none_or(|r| r.iter().all(|e| *e == 0.0)))
+            .map(|(k, _)| k.clone())
+            .collect();
+        for k in idle {
+            self.cpu.remove(&k);
+            self.res_data.remove(&k);
+        }
+    }
+
+    /// 按时间序列的均值排序，取前top个命令名称
+    pub fn top_by_mean(values: &BTreeMap<String, Vec<f32>>, top: usize) -> Vec<(String, f32)> {
+        let mut vec: Vec<(String, f32)> = values
+            .iter()
+            .map(|(k, v)| {
+                let mean = if v.is_empty() { 0.0 } else { v.iter().sum::<f32>() / v.len() as f32 };
+                (k.clone(), mean)
+            })
+            .collect();
+        vec.sort_by(|a, b| b.1.total_cmp(&a.1));
+        vec.truncate(top);
+        vec
+    }
+
+    pub(crate) fn echartjs_overtime(&self, values: &BTreeMap<String, Vec<f32>>, top: usize, scale: f32) -> Vec<(String, Vec<Point>)> {
+        let series: Vec<(String, Vec<f32>)> = Self::top_by_mean(values, top)
+            .into_iter()
+            .map(|(name, _)| {
+                let ys = values.get(&name).unwrap().iter().map(|y| *y / scale).collect();
+                (name, ys)
+            })
+            .collect();
+        // 各进程的曲线按相同的时间点降采样
+        let ys: Vec<Vec<f32>> = series.iter().map(|(_, ys)| ys.clone()).collect();
+        let idxs = match self.max_points {
+            Some(max) => downsample::lttb_shared(&ys, max),
+            None => (0..self.zzzz.len()).collect(),
+        };
+        series
+            .into_iter()
+            .map(|(name, ys)| {
+                let points = idxs
+                    .iter()
+                    .filter(|i| **i < ys.len())
+                    .map(|i| Point::new(format!("{}", self.zzzz[*i].format(POINT_X_FORMAT)), ys[*i]))
+                    .collect();
+                (name, points)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOP_HEADER: &str = "TOP,+PID,Time,%CPU,%Usr,%Sys,Size,ResSet,ResText,ResData,ShdLib,MinorFault,MajorFault,Command";
+
+    fn push_top(builder: &mut TopBuilder, line: &str, lineno: usize) -> Result<(), NmonError> {
+        let snapshots: HashMap<String, usize> = [("T0001".to_string(), 0), ("T0002".to_string(), 1)].into_iter().collect();
+        let vec: Vec<&str> = line.split(',').collect();
+        builder.push_top(&vec, line, lineno, &snapshots)
+    }
+
+    fn zzzz() -> Vec<DateTime<FixedOffset>> {
+        ["2020-06-16T17:40:00+08:00", "2020-06-16T17:40:10+08:00"]
+            .iter()
+            .map(|s| DateTime::parse_from_rfc3339(s).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn cpu_is_summed_by_command() {
+        let mut builder = TopBuilder::new("app01.nmon");
+        for (i, line) in [
+            TOP_HEADER,
+            "TOP,0001234,T0001,50.5,45.0,5.5,1,1,1,1024,1,1,0,java",
+            "TOP,0001235,T0001,20.0,18.0,2.0,1,1,1,2048,1,1,0,java",
+            "TOP,0000042,T0001,3.0,2.0,1.0,1,1,1,100,1,1,0,nginx",
+            "TOP,0001234,T0002,10.0,9.0,1.0,1,1,1,1024,1,1,0,java",
+        ].iter().enumerate() {
+            push_top(&mut builder, line, i + 1).unwrap();
+        }
+        let top = builder.build(&zzzz(), None);
+        assert_eq!(top.cpu()["java"], vec![70.5, 10.0]);
+        assert_eq!(top.res_data()["java"], vec![3072.0, 1024.0]);
+        // 某进程在部分时间序列里不存在时为0
+        assert_eq!(top.cpu()["nginx"], vec![3.0, 0.0]);
+        let ranked: Vec<String> = TopProcesses::top_by_mean(top.cpu(), 1).into_iter().map(|(name, _)| name).collect();
+        assert_eq!(ranked, vec!["java"]);
+    }
+
+    #[test]
+    fn bad_value_is_an_error() {
+        let mut builder = TopBuilder::new("app01.nmon");
+        push_top(&mut builder, TOP_HEADER, 1).unwrap();
+        let err = push_top(&mut builder, "TOP,0001234,T0001,abc,45.0,5.5,1,1,1,1024,1,1,0,java", 2).unwrap_err();
+        assert!(matches!(err, NmonError::Value { ref section, ref column, line: 2, .. } if section == "TOP" && column == "%CPU"));
+        // 出错的行不计入
+        let top = builder.build(&zzzz(), None);
+        assert!(top.is_empty());
+    }
+
+    #[test]
+    fn uarg_full_command_keeps_commas() {
+        let mut builder = TopBuilder::new("app01.nmon");
+        for line in [
+            "UARG,+Time,PID,PPID,COMM,THCOUNT,USER,GROUP,FullCommand",
+            "UARG,T0001,1234,1,java,50,app,app,/usr/bin/java -Xmx2g -Dhosts=a,b,c -jar app.jar",
+            "UARG,T0001,1235,1,java,20,app,app,/usr/bin/java -jar batch.jar",
+            "UARG,T0001,1236,1",
+        ] {
+            let vec: Vec<&str> = line.split(',').collect();
+            builder.push_uarg(&vec, line);
+        }
+        let top = builder.build(&zzzz(), None);
+        let commands: Vec<&str> = top.full_commands("java").unwrap().iter().map(|s| s.as_str()).collect();
+        assert_eq!(commands, vec!["/usr/bin/java -Xmx2g -Dhosts=a,b,c -jar app.jar", "/usr/bin/java -jar batch.jar"]);
+        assert!(top.full_commands("nginx").is_none());
+    }
+}
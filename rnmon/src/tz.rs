@@ -0,0 +1,150 @@
+use chrono::DateTime;
+use chrono::FixedOffset;
+use chrono::Local;
+use chrono::NaiveDateTime;
+use chrono::Offset;
+use chrono::TimeZone;
+use regex::Regex;
+use std::path::Path;
+use std::str::FromStr;
+
+/// nmon的ZZZZ时间序列为服务器本地时间，不带时区，解析时需指定服务器所在的时区
+#[derive(Debug, Clone, Default)]
+pub enum NmonTz {
+    /// 运行rnmon的机器的时区
+    #[default]
+    Local,
+    /// 固定时区，比如: +08:00、-0500、UTC
+    Fixed(FixedOffset),
+    /// IANA时区名称，比如: Asia/Shanghai
+    Iana(chrono_tz::Tz),
+}
+
+impl FromStr for NmonTz {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("local") {
+            return Ok(NmonTz::Local);
+        }
+        if s.eq_ignore_ascii_case("utc") || s.eq_ignore_ascii_case("z") {
+            return Ok(NmonTz::Fixed(FixedOffset::east_opt(0).unwrap()));
+        }
+        if s.starts_with('+') || s.starts_with('-') {
+            let digits: String = s[1..].chars().filter(|c| c.is_ascii_digit()).collect();
+            let (hours, minutes) = match digits.len() {
+                1 | 2 => (digits.parse::<i32>().ok(), Some(0)),
+                3 | 4 => (digits[..digits.len() - 2].parse::<i32>().ok(), digits[digits.len() - 2..].parse::<i32>().ok()),
+                _ => (None, None),
+            };
+            let secs = match (hours, minutes) {
+                (Some(h), Some(m)) if m < 60 => (h * 3600 + m * 60) * if s.starts_with('-') { -1 } else { 1 },
+                _ => return Err(format!("时区'{}'格式不正确，固定时区的格式为: +08:00、+0800、-05", s)),
+            };
+            return FixedOffset::east_opt(secs)
+                .map(NmonTz::Fixed)
+                .ok_or(format!("时区'{}'超出范围", s));
+        }
+        s.parse::<chrono_tz::Tz>()
+            .map(NmonTz::Iana)
+            .map_err(|e| format!("时区'{}'即不是固定时区也不是IANA时区名称: {}", s, e))
+    }
+}
+
+impl NmonTz {
+    /// 把服务器本地时间转换为带时区的时间，夏令时切换时取较早的时间
+    pub fn localize(&self, naive: &NaiveDateTime) -> DateTime<FixedOffset> {
+        match self {
+            NmonTz::Local => Self::localize_in(&Local, naive),
+            NmonTz::Fixed(offset) => Self::localize_in(offset, naive),
+            NmonTz::Iana(tz) => Self::localize_in(tz, naive),
+        }
+    }
+
+    fn localize_in<T: TimeZone>(tz: &T, naive: &NaiveDateTime) -> DateTime<FixedOffset> {
+        match tz.from_local_datetime(naive).earliest() {
+            Some(dt) => dt.fixed_offset(),
+            None => {
+                // 夏令时开始时跳过的时间，按该时刻的时区偏移量处理
+                let offset = tz.offset_from_utc_datetime(naive).fix();
+                offset.from_local_datetime(naive).unwrap()
+            }
+        }
+    }
+}
+
+/// 某些nmon文件单独指定时区，格式为: REGEX=TZ，REGEX匹配nmon文件名
+#[derive(Debug, Clone)]
+pub struct FileTz {
+    pattern: Regex,
+    tz: NmonTz,
+}
+
+impl FromStr for FileTz {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (pattern, tz) = s
+            .rsplit_once('=')
+            .ok_or(format!("'{}'格式不正确，应为: REGEX=TZ，比如: db01=Asia/Shanghai", s))?;
+        let pattern = Regex::new(pattern).map_err(|e| format!("'{}'正则表达式不正确: {}", pattern, e))?;
+        let tz = NmonTz::from_str(tz)?;
+        Ok(Self { pattern, tz })
+    }
+}
+
+impl FileTz {
+    /// 按nmon文件名取时区，匹配多个时取第一个，都不匹配时取默认时区
+    pub fn select<'a>(overrides: &'a [FileTz], path: &Path, default: &'a NmonTz) -> &'a NmonTz {
+        let filename = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        overrides
+            .iter()
+            .find(|file_tz| file_tz.pattern.is_match(&filename))
+            .map(|file_tz| &file_tz.tz)
+            .unwrap_or(default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offset_secs(tz: &str) -> i32 {
+        match NmonTz::from_str(tz).unwrap() {
+            NmonTz::Fixed(offset) => offset.local_minus_utc(),
+            other => panic!("{} -> {:?}", tz, other),
+        }
+    }
+
+    #[test]
+    fn parse_fixed_offset() {
+        assert_eq!(offset_secs("+08:00"), 8 * 3600);
+        assert_eq!(offset_secs("+0800"), 8 * 3600);
+        assert_eq!(offset_secs("-05"), -5 * 3600);
+        assert_eq!(offset_secs("+05:30"), 5 * 3600 + 30 * 60);
+        assert_eq!(offset_secs("UTC"), 0);
+        assert_eq!(offset_secs("z"), 0);
+        assert!(NmonTz::from_str("+08:75").is_err());
+        assert!(NmonTz::from_str("+99").is_err());
+    }
+
+    #[test]
+    fn parse_iana_name() {
+        assert!(matches!(NmonTz::from_str("local").unwrap(), NmonTz::Local));
+        assert!(matches!(NmonTz::from_str("Asia/Shanghai").unwrap(), NmonTz::Iana(chrono_tz::Asia::Shanghai)));
+        assert!(NmonTz::from_str("Mars/Olympus").is_err());
+    }
+
+    #[test]
+    fn localize_in_timezone() {
+        let naive = NaiveDateTime::parse_from_str("2020-06-16 17:41:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        let shanghai = NmonTz::from_str("Asia/Shanghai").unwrap().localize(&naive);
+        assert_eq!(shanghai.to_rfc3339(), "2020-06-16T17:41:00+08:00");
+        let fixed = NmonTz::from_str("-0500").unwrap().localize(&naive);
+        assert_eq!(fixed.to_rfc3339(), "2020-06-16T17:41:00-05:00");
+        // 夏令时: 纽约6月为-04:00
+        let new_york = NmonTz::from_str("America/New_York").unwrap().localize(&naive);
+        assert_eq!(new_york.to_rfc3339(), "2020-06-16T17:41:00-04:00");
+    }
+}
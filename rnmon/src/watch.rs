@@ -0,0 +1,193 @@
+use log::debug;
+use log::info;
+use log::warn;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::BufRead;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::FileTz;
+use crate::NmonArgs;
+use crate::NmonData;
+use crate::NmonParser;
+use crate::ParseOptions;
+use crate::RunNmonArgs;
+use crate::TimeWindow;
+use crate::error::NmonError;
+use crate::input;
+use crate::input::InputFilter;
+use crate::input::NmonInput;
+
+/// 正在写入的nmon文件，每次只读取新增的数据，只解析新增的完整行，最后一行没写完时等下次再读
+struct Tail {
+    path: PathBuf,
+    // 已读取的字节数
+    offset: u64,
+    // 已解析的数据，只保留各指标解析后的数据，不保留读取的行
+    parser: NmonParser,
+    // 最后一行没写完的部分
+    pending: Vec<u8>,
+    changed: bool,
+}
+
+impl Tail {
+    fn new(path: &Path, parser: NmonParser) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            offset: 0,
+            parser,
+            pending: Vec::new(),
+            changed: false,
+        }
+    }
+
+    /// 读取新增的数据并解析新增的完整行，有新增的完整行时changed为true。
+    /// nmon文件被截断或重新生成时，按新的parser从头解析
+    fn poll(&mut self, new_parser: impl FnOnce() -> NmonParser) -> Result<(), NmonError> {
+        let io_err = |e: std::io::Error| NmonError::Io { path: self.path.clone(), source: e };
+        let mut f = File::open(&self.path).map_err(io_err)?;
+        let len = f.metadata().map_err(io_err)?.len();
+        if len < self.offset {
+            // nmon文件被截断或重新生成，从头读取
+            info!("{}文件变小了，重新读取", self.path.display());
+            self.offset = 0;
+            self.parser = new_parser();
+            self.pending.clear();
+            self.changed = true;
+        }
+        if len == self.offset {
+            return Ok(());
+        }
+        f.seek(SeekFrom::Start(self.offset)).map_err(io_err)?;
+        let read = f.take(len - self.offset).read_to_end(&mut self.pending).map_err(io_err)?;
+        self.offset += read as u64;
+        if let Some(pos) = self.pending.iter().rposition(|b| *b == b'\n') {
+            for line in self.pending.drain(..=pos).collect::<Vec<u8>>().lines() {
+                let result = match line {
+                    Ok(line) => self.parser.push_line(&line),
+                    Err(e) => self.parser.push_error(e),
+                };
+                // 严格模式下出错的行不加入已解析的数据，继续解析之后的行
+                if let Err(e) = result {
+                    warn!("{}，跳过该行", e);
+                }
+            }
+            self.changed = true;
+        }
+        debug!("{}文件已读取{}字节，未写完的数据{}字节", self.path.display(), self.offset, self.pending.len());
+        Ok(())
+    }
+}
+
+/// 某个nmon文件的parser，--tz-file可单独指定时区
+fn new_parser(path: &Path, cell: &HashSet<String>, run_nmon_args: &RunNmonArgs) -> NmonParser {
+    let filename = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_else(|| path.display().to_string());
+    let options = ParseOptions {
+        disk_filter: run_nmon_args.disk_filter.clone(),
+        lenient: run_nmon_args.lenient,
+        tz: FileTz::select(&run_nmon_args.tz_file, path, &run_nmon_args.tz).clone(),
+        window: TimeWindow::new(run_nmon_args.from.clone(), run_nmon_args.to.clone()),
+        max_points: run_nmon_args.max_points.map(|n| n as usize),
+    };
+    NmonParser::new(&filename, cell, &options)
+}
+
+/// 跟踪正在写入的nmon文件，有新的快照时重新生成报告
+pub struct Watcher {
+    nmon_args: NmonArgs,
+    cell: HashSet<String>,
+    filter: InputFilter,
+    tails: BTreeMap<PathBuf, Tail>,
+    // 与datas一一对应
+    paths: Vec<PathBuf>,
+    datas: Vec<NmonData>,
+}
+
+impl Watcher {
+    pub fn new(nmon_args: NmonArgs, cell: HashSet<String>) -> Result<Self, NmonError> {
+        let run_nmon_args = nmon_args.run_nmon_args();
+        let filter = InputFilter::new(run_nmon_args.recursive, &run_nmon_args.include, &run_nmon_args.exclude)?;
+        Ok(Self {
+            nmon_args,
+            cell,
+            filter,
+            tails: BTreeMap::new(),
+            paths: Vec::new(),
+            datas: Vec::new(),
+        })
+    }
+
+    pub fn datas(&self) -> &[NmonData] {
+        &self.datas
+    }
+
+    /// 扫描新的nmon文件并解析新增的数据，有变化时按已解析的数据重新生成变化的nmon文件的NmonData，返回是否有变化。
+    /// 只跟踪未压缩的nmon文件，nmon文件读取失败或数据不足以生成NmonData时保留上一次的数据
+    pub fn poll(&mut self) -> Result<bool, NmonError> {
+        let mut skipped = Vec::new();
+        for input in input::discover(&self.nmon_args.path, &self.filter, true, &mut skipped)? {
+            match input {
+                NmonInput::Plain(path) => {
+                    if !self.tails.contains_key(&path) {
+                        info!("开始跟踪{}文件", path.display());
+                        let tail = Tail::new(&path, new_parser(&path, &self.cell, self.nmon_args.run_nmon_args()));
+                        self.tails.insert(path, tail);
+                    }
+                }
+                _ => debug!("{}不是正在写入的nmon文件，watch时忽略", input.path().display()),
+            }
+        }
+        for err in skipped.iter() {
+            warn!("{}", err);
+        }
+
+        let mut changed = false;
+        let (cell, run_nmon_args) = (&self.cell, self.nmon_args.run_nmon_args());
+        for (path, tail) in self.tails.iter_mut() {
+            if let Err(e) = tail.poll(|| new_parser(path, cell, run_nmon_args)) {
+                warn!("{}，保留上一次的数据", e);
+            }
+            changed |= tail.changed;
+        }
+        if !changed {
+            return Ok(false);
+        }
+
+        let mut old: HashMap<PathBuf, NmonData> = self.paths.drain(..).zip(self.datas.drain(..)).collect();
+        for (path, tail) in self.tails.iter_mut() {
+            let previous = old.remove(path);
+            if !tail.changed {
+                if let Some(ndata) = previous {
+                    self.paths.push(path.clone());
+                    self.datas.push(ndata);
+                }
+                continue;
+            }
+            tail.changed = false;
+            match tail.parser.snapshot() {
+                // 还没有完整的快照时，不生成图表
+                Ok(ndata) if ndata.measurements().values().all(|m| m.zzzz().is_empty()) => {
+                    debug!("{}文件还没有完整的快照", path.display());
+                }
+                Ok(ndata) => {
+                    self.paths.push(path.clone());
+                    self.datas.push(ndata);
+                }
+                Err(e) => {
+                    warn!("{}，保留上一次的数据", e);
+                    if let Some(ndata) = previous {
+                        self.paths.push(path.clone());
+                        self.datas.push(ndata);
+                    }
+                }
+            }
+        }
+        Ok(true)
+    }
+}
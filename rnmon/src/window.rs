@@ -0,0 +1,165 @@
+use chrono::DateTime;
+use chrono::Duration;
+use chrono::FixedOffset;
+use chrono::NaiveDateTime;
+use chrono::NaiveTime;
+use std::ops::Range;
+use std::str::FromStr;
+
+use crate::tz::NmonTz;
+
+/// --from/--to的取值，可以是绝对时间，也可以是相对nmon文件开始(+)或结束(-)的偏移量
+#[derive(Debug, Clone)]
+pub enum TimeBound {
+    /// +60s，相对第一个ZZZZ时间序列向后偏移
+    FromStart(Duration),
+    /// -30s，相对最后一个ZZZZ时间序列向前偏移
+    FromEnd(Duration),
+    /// 2020-06-16T17:41:00+08:00，带时区的时间
+    At(DateTime<FixedOffset>),
+    /// 2020-06-16 17:41:00，按nmon文件的时区处理
+    Naive(NaiveDateTime),
+    /// 17:41:00，日期取第一个ZZZZ时间序列的日期
+    Time(NaiveTime),
+}
+
+impl FromStr for TimeBound {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(offset) = s.strip_prefix('+') {
+            return parse_duration(offset).map(TimeBound::FromStart);
+        }
+        if let Some(offset) = s.strip_prefix('-') {
+            return parse_duration(offset).map(TimeBound::FromEnd);
+        }
+        if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+            return Ok(TimeBound::At(dt));
+        }
+        for fmt in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S"] {
+            if let Ok(ndt) = NaiveDateTime::parse_from_str(s, fmt) {
+                return Ok(TimeBound::Naive(ndt));
+            }
+        }
+        if let Ok(t) = NaiveTime::parse_from_str(s, "%H:%M:%S") {
+            return Ok(TimeBound::Time(t));
+        }
+        Err(format!(
+            "'{}'格式不正确，应为偏移量(+60s、-30s、+5m、+1h)或时间(2020-06-16 17:41:00、2020-06-16T17:41:00+08:00、17:41:00)",
+            s
+        ))
+    }
+}
+
+/// 60、60s、5m、1h
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    let (num, unit) = match s.find(|c: char| !c.is_ascii_digit()) {
+        Some(idx) => s.split_at(idx),
+        None => (s, "s"),
+    };
+    let num = i64::from_str(num).map_err(|_| format!("偏移量'{}'格式不正确，应为: 60s、5m、1h", s))?;
+    match unit {
+        "s" => Ok(Duration::seconds(num)),
+        "m" => Ok(Duration::minutes(num)),
+        "h" => Ok(Duration::hours(num)),
+        _ => Err(format!("偏移量'{}'的单位不正确，只支持s、m、h", s)),
+    }
+}
+
+impl TimeBound {
+    /// 按nmon文件的ZZZZ时间序列和时区，转换为具体的时间
+    fn resolve(&self, zzzz: &[DateTime<FixedOffset>], tz: &NmonTz) -> Option<DateTime<FixedOffset>> {
+        match self {
+            TimeBound::FromStart(d) => zzzz.first().map(|first| *first + *d),
+            TimeBound::FromEnd(d) => zzzz.last().map(|last| *last - *d),
+            TimeBound::At(dt) => Some(*dt),
+            TimeBound::Naive(ndt) => Some(tz.localize(ndt)),
+            TimeBound::Time(t) => zzzz.first().map(|first| tz.localize(&first.date_naive().and_time(*t))),
+        }
+    }
+}
+
+/// 只分析nmon文件的某个时间段，比如去掉JMeter线程加载(ramp-up)和结束后的空闲时间
+#[derive(Debug, Clone, Default)]
+pub struct TimeWindow {
+    from: Option<TimeBound>,
+    to: Option<TimeBound>,
+}
+
+impl TimeWindow {
+    pub fn new(from: Option<TimeBound>, to: Option<TimeBound>) -> Self {
+        Self { from, to }
+    }
+
+    pub fn is_all(&self) -> bool {
+        self.from.is_none() && self.to.is_none()
+    }
+
+    /// 时间段内的ZZZZ时间序列下标范围，包含--from和--to
+    pub fn range(&self, zzzz: &[DateTime<FixedOffset>], tz: &NmonTz) -> Range<usize> {
+        let start = match self.from.as_ref().and_then(|from| from.resolve(zzzz, tz)) {
+            Some(from) => zzzz.iter().position(|x| *x >= from).unwrap_or(zzzz.len()),
+            None => 0,
+        };
+        let end = match self.to.as_ref().and_then(|to| to.resolve(zzzz, tz)) {
+            Some(to) => zzzz.iter().position(|x| *x > to).unwrap_or(zzzz.len()),
+            None => zzzz.len(),
+        };
+        start..end.max(start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_offset() {
+        assert!(matches!(TimeBound::from_str("+60s").unwrap(), TimeBound::FromStart(d) if d == Duration::seconds(60)));
+        assert!(matches!(TimeBound::from_str("-30s").unwrap(), TimeBound::FromEnd(d) if d == Duration::seconds(30)));
+        assert!(matches!(TimeBound::from_str("+5m").unwrap(), TimeBound::FromStart(d) if d == Duration::minutes(5)));
+        assert!(matches!(TimeBound::from_str("-1h").unwrap(), TimeBound::FromEnd(d) if d == Duration::hours(1)));
+        assert!(matches!(TimeBound::from_str("+90").unwrap(), TimeBound::FromStart(d) if d == Duration::seconds(90)));
+        assert!(TimeBound::from_str("+5d").is_err());
+        assert!(TimeBound::from_str("+s").is_err());
+    }
+
+    #[test]
+    fn parse_time() {
+        match TimeBound::from_str("2020-06-16T17:41:00+08:00").unwrap() {
+            TimeBound::At(dt) => assert_eq!(dt.to_rfc3339(), "2020-06-16T17:41:00+08:00"),
+            other => panic!("{:?}", other),
+        }
+        for s in ["2020-06-16 17:41:00", "2020-06-16T17:41:00"] {
+            match TimeBound::from_str(s).unwrap() {
+                TimeBound::Naive(ndt) => assert_eq!(ndt.to_string(), "2020-06-16 17:41:00"),
+                other => panic!("{:?}", other),
+            }
+        }
+        match TimeBound::from_str("17:41:00").unwrap() {
+            TimeBound::Time(t) => assert_eq!(t, NaiveTime::from_hms_opt(17, 41, 0).unwrap()),
+            other => panic!("{:?}", other),
+        }
+        assert!(TimeBound::from_str("yesterday").is_err());
+    }
+
+    #[test]
+    fn range_of_window() {
+        let tz = NmonTz::from_str("+08:00").unwrap();
+        let zzzz: Vec<DateTime<FixedOffset>> = (0..10)
+            .map(|i| DateTime::parse_from_rfc3339("2020-06-16T17:40:00+08:00").unwrap() + Duration::seconds(i * 10))
+            .collect();
+        let range = |from: Option<&str>, to: Option<&str>| {
+            TimeWindow::new(from.map(|s| s.parse().unwrap()), to.map(|s| s.parse().unwrap())).range(&zzzz, &tz)
+        };
+        assert_eq!(range(None, None), 0..10);
+        assert_eq!(range(Some("+20s"), Some("-30s")), 2..7);
+        assert_eq!(range(Some("+15s"), None), 2..10);
+        assert_eq!(range(Some("17:40:30"), Some("2020-06-16 17:40:50")), 3..6);
+        assert_eq!(range(Some("2020-06-16T09:40:40Z"), None), 4..10);
+        // 时间段在nmon文件之外或--from晚于--to时为空
+        assert!(range(Some("+1h"), None).is_empty());
+        assert!(range(Some("-10s"), Some("+10s")).is_empty());
+    }
+}